@@ -0,0 +1,186 @@
+use std::io;
+
+/// Writer of arbitrary-width bit fields to the underlying [`io::Write`].
+///
+/// The bits are packed into bytes starting from the least significant bit,
+/// so writing the bits of the `u64` words one by one gives the same output as writing the words as-is (in little-endian).
+/// The bits are buffered and written by whole 64-bit words, so the last bits are written only by
+/// [`flush`](BitWriter::flush) (or [`into_inner`](BitWriter::into_inner)), which pads them with zeros to a full byte.
+/// [`align`](BitWriter::align) also writes and pads them, but does not flush the underlying writer.
+pub struct BitWriter<W: io::Write> {
+    output: W,
+    buffer: u64,    // bits waiting to be written
+    len: u8 // number of bits in buffer, always less than 64
+}
+
+impl<W: io::Write> BitWriter<W> {
+    /// Constructs the writer that writes to the given `output`.
+    #[inline] pub fn new(output: W) -> Self { Self { output, buffer: 0, len: 0 } }
+
+    /// Writes `len` (at most 64) lowest bits of `bits`.
+    pub fn write_bits(&mut self, bits: u64, len: u8) -> io::Result<()> {
+        debug_assert!(len <= 64);
+        if len == 0 { return Ok(()); }
+        let bits = if len == 64 { bits } else { bits & ((1u64 << len) - 1) };
+        self.buffer |= bits << self.len;
+        let new_len = self.len + len;
+        if new_len < 64 {
+            self.len = new_len;
+            return Ok(());
+        }
+        self.output.write_all(&self.buffer.to_le_bytes())?;
+        self.buffer = if self.len == 0 { 0 } else { bits >> (64 - self.len) };
+        self.len = new_len - 64;
+        Ok(())
+    }
+
+    /// Writes a single `bit`.
+    #[inline] pub fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.write_bits(bit as u64, 1)
+    }
+
+    /// Writes `value` in unary code, i.e. as `value` zeros followed by a one.
+    pub fn write_unary(&mut self, mut value: u64) -> io::Result<()> {
+        while value >= 63 {
+            self.write_bits(0, 63)?;
+            value -= 63;
+        }
+        self.write_bits(1 << value, value as u8 + 1)
+    }
+
+    /// Returns the number of bits that wait in the buffer to be written.
+    #[inline] pub fn pending_bits(&self) -> u8 { self.len }
+
+    /// Writes all buffered bits, padding them with zeros to a full byte.
+    /// The bits written after that start with a new byte.
+    pub fn align(&mut self) -> io::Result<()> {
+        let bytes = (self.len as usize).div_ceil(8);
+        self.output.write_all(&self.buffer.to_le_bytes()[..bytes])?;
+        self.buffer = 0;
+        self.len = 0;
+        Ok(())
+    }
+
+    /// Writes all buffered bits, padding them with zeros to a full byte (see [`align`](BitWriter::align)),
+    /// and flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.align()?;
+        self.output.flush()
+    }
+
+    /// Flushes the buffered bits (see [`flush`](BitWriter::flush)) and returns the underlying writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.flush()?;
+        Ok(self.output)
+    }
+}
+
+/// Reader of arbitrary-width bit fields (written by [`BitWriter`]) from the underlying [`io::Read`].
+///
+/// The input is read byte by byte, so reading from a file should be buffered (by [`io::BufReader`]).
+pub struct BitReader<R: io::Read> {
+    input: R,
+    buffer: u128,   // bits read from input but not returned yet
+    len: u8 // number of bits in buffer
+}
+
+impl<R: io::Read> BitReader<R> {
+    /// Constructs the reader that reads from the given `input`.
+    #[inline] pub fn new(input: R) -> Self { Self { input, buffer: 0, len: 0 } }
+
+    /// Reads the next byte from the input to the buffer.
+    #[inline] fn fill_byte(&mut self) -> io::Result<()> {
+        let mut byte = 0u8;
+        self.input.read_exact(std::slice::from_mut(&mut byte))?;
+        self.buffer |= (byte as u128) << self.len;
+        self.len += 8;
+        Ok(())
+    }
+
+    /// Reads `len` (at most 64) bits and returns them as the lowest bits of the result.
+    pub fn read_bits(&mut self, len: u8) -> io::Result<u64> {
+        debug_assert!(len <= 64);
+        while self.len < len { self.fill_byte()?; }
+        let result = (self.buffer & ((1u128 << len) - 1)) as u64;
+        self.buffer >>= len;
+        self.len -= len;
+        Ok(result)
+    }
+
+    /// Reads a single bit.
+    #[inline] pub fn read_bit(&mut self) -> io::Result<bool> {
+        self.read_bits(1).map(|b| b != 0)
+    }
+
+    /// Reads a value written in unary code (see [`BitWriter::write_unary`]).
+    pub fn read_unary(&mut self) -> io::Result<u64> {
+        let mut result = 0;
+        while self.buffer == 0 {
+            result += self.len as u64;
+            self.buffer = 0;
+            self.len = 0;
+            self.fill_byte()?;
+        }
+        let zeros = self.buffer.trailing_zeros() as u8;
+        self.buffer >>= zeros + 1;
+        self.len -= zeros + 1;
+        Ok(result + zeros as u64)
+    }
+
+    /// Skips the bits that remain to the end of the current byte,
+    /// i.e. the padding written by [`BitWriter::align`] or [`BitWriter::flush`].
+    #[inline] pub fn align(&mut self) {
+        let padding = self.len % 8;
+        self.buffer >>= padding;
+        self.len -= padding;
+    }
+
+    /// Returns the underlying reader. The bits that are already read from it but not returned yet are lost.
+    #[inline] pub fn into_inner(self) -> R { self.input }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_read_bits() {
+        let mut w = BitWriter::new(Vec::new());
+        w.write_bits(0b101, 3).unwrap();
+        w.write_bit(true).unwrap();
+        w.write_bits(u64::MAX, 64).unwrap();
+        w.write_unary(0).unwrap();
+        w.write_unary(5).unwrap();
+        w.write_unary(200).unwrap();
+        w.write_bits(0x1234_5678_9ABC_DEF0, 64).unwrap();
+        w.write_bits(0b11, 2).unwrap();
+        w.flush().unwrap();
+        w.write_bits(0b1, 1).unwrap();
+        let buff = w.into_inner().unwrap();
+        assert_eq!(buff.len(), (3 + 1 + 64 + 1 + 6 + 201 + 64 + 2usize).div_ceil(8) + 1);
+        let mut r = BitReader::new(&buff[..]);
+        assert_eq!(r.read_bits(3).unwrap(), 0b101);
+        assert!(r.read_bit().unwrap());
+        assert_eq!(r.read_bits(64).unwrap(), u64::MAX);
+        assert_eq!(r.read_unary().unwrap(), 0);
+        assert_eq!(r.read_unary().unwrap(), 5);
+        assert_eq!(r.read_unary().unwrap(), 200);
+        assert_eq!(r.read_bits(64).unwrap(), 0x1234_5678_9ABC_DEF0);
+        assert_eq!(r.read_bits(2).unwrap(), 0b11);
+        r.align();
+        assert_eq!(r.read_bits(1).unwrap(), 1);
+        r.align();
+        assert!(r.read_bit().is_err());
+    }
+
+    #[test]
+    fn same_as_words() {
+        let words = [0x0123_4567_89AB_CDEFu64, 0xFEDC_BA98_7654_3210];
+        let mut w = BitWriter::new(Vec::new());
+        for word in words {
+            for i in 0..64 { w.write_bit(word & (1 << i) != 0).unwrap(); }
+        }
+        let buff = w.into_inner().unwrap();
+        assert_eq!(buff, words.iter().flat_map(|w| w.to_le_bytes()).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,298 @@
+use std::io;
+use crate::{BitReader, BitWriter, Serializer};
+
+/// Trait implemented by universal (bit-oriented, variable-length) codes of `u64` values.
+///
+/// Each type that implements `BitCode` also implements [`Serializer`] for `u32` and `u64` (and thus `usize`).
+/// Single values are padded to full bytes, but arrays (see [`Serializer::write_array`] and [`Serializer::read_array`])
+/// are bit-packed, with only the end of the whole array padded.
+pub trait BitCode: Copy {
+    /// Returns the number of bits of the code of `value`.
+    fn bits(value: u64) -> u64;
+
+    /// Writes the code of `value` to the given bit `output`.
+    fn write_code<W: io::Write>(output: &mut BitWriter<W>, value: u64) -> io::Result<()>;
+
+    /// Reads the code of a value from the given bit `input`.
+    fn read_code<R: io::Read>(input: &mut BitReader<R>) -> io::Result<u64>;
+}
+
+/// Returns the number of bits needed to store `value + 1` (1 to 65).
+#[inline] fn bit_len(value: u64) -> u32 {
+    if value == u64::MAX { 65 } else { u64::BITS - (value + 1).leading_zeros() }
+}
+
+/// Writes `len` (at most 128) lowest bits of `bits`.
+#[inline] fn write_wide<W: io::Write>(output: &mut BitWriter<W>, bits: u128, len: u32) -> io::Result<()> {
+    if len > 64 {
+        output.write_bits(bits as u64, 64)?;
+        output.write_bits((bits >> 64) as u64, (len - 64) as u8)
+    } else {
+        output.write_bits(bits as u64, len as u8)
+    }
+}
+
+/// Reads `len` (at most 128) bits.
+#[inline] fn read_wide<R: io::Read>(input: &mut BitReader<R>, len: u32) -> io::Result<u128> {
+    if len > 64 {
+        let low = input.read_bits(64)? as u128;
+        Ok(low | (input.read_bits((len - 64) as u8)? as u128) << 64)
+    } else {
+        input.read_bits(len as u8).map(|v| v as u128)
+    }
+}
+
+/// Converts `value` read from the input to `u64`, or returns error if it is too large.
+#[inline] fn to_u64(value: u128) -> io::Result<u64> {
+    value.try_into().map_err(|_| io::ErrorKind::InvalidData.into())
+}
+
+/// Elias gamma code of `value + 1`: unary code of its length followed by its bits, without the leading one.
+/// It uses *2⌊log2(value+1)⌋+1* bits.
+///
+/// See: P. Elias, *Universal codeword sets and representations of the integers*,
+/// IEEE Transactions on Information Theory 21(2) (1975), 194–203. <https://doi.org/10.1109/TIT.1975.1055349>
+#[derive(Clone, Copy)]
+pub struct EliasGamma;
+
+impl BitCode for EliasGamma {
+    #[inline] fn bits(value: u64) -> u64 { 2 * bit_len(value) as u64 - 1 }
+
+    fn write_code<W: io::Write>(output: &mut BitWriter<W>, value: u64) -> io::Result<()> {
+        let len = bit_len(value) - 1;
+        output.write_unary(len as u64)?;
+        output.write_bits(value.wrapping_add(1), len as u8)
+    }
+
+    fn read_code<R: io::Read>(input: &mut BitReader<R>) -> io::Result<u64> {
+        let len = input.read_unary()?;
+        if len > 64 { return Err(io::ErrorKind::InvalidData.into()); }
+        to_u64(((1u128 << len) | input.read_bits(len as u8)? as u128) - 1)
+    }
+}
+
+/// Elias delta code of `value + 1`: [`EliasGamma`] code of its length followed by its bits, without the leading one.
+/// It uses *⌊log2(value+1)⌋ + 2⌊log2(⌊log2(value+1)⌋+1)⌋ + 1* bits.
+///
+/// See: P. Elias, *Universal codeword sets and representations of the integers*,
+/// IEEE Transactions on Information Theory 21(2) (1975), 194–203. <https://doi.org/10.1109/TIT.1975.1055349>
+#[derive(Clone, Copy)]
+pub struct EliasDelta;
+
+impl BitCode for EliasDelta {
+    #[inline] fn bits(value: u64) -> u64 {
+        let len = bit_len(value) as u64 - 1;
+        EliasGamma::bits(len) + len
+    }
+
+    fn write_code<W: io::Write>(output: &mut BitWriter<W>, value: u64) -> io::Result<()> {
+        let len = bit_len(value) - 1;
+        EliasGamma::write_code(output, len as u64)?;
+        output.write_bits(value.wrapping_add(1), len as u8)
+    }
+
+    fn read_code<R: io::Read>(input: &mut BitReader<R>) -> io::Result<u64> {
+        let len = EliasGamma::read_code(input)?;
+        if len > 64 { return Err(io::ErrorKind::InvalidData.into()); }
+        to_u64(((1u128 << len) | input.read_bits(len as u8)? as u128) - 1)
+    }
+}
+
+/// Golomb-Rice code with parameter `K` (less than 64, which is checked at compile time): unary code of `value >> K` followed by `K` lowest bits of `value`.
+/// It uses *(value >> K) + K + 1* bits, so `K` should be close to *log2* of the mean value.
+///
+/// See: R. F. Rice, *Some practical universal noiseless coding techniques*, JPL Publication 79-22, 1979.
+#[derive(Clone, Copy)]
+pub struct GolombRice<const K: u8>;
+
+impl<const K: u8> GolombRice<K> {
+    /// Fails at compile time if `K` is not less than 64.
+    const VALID_K: () = assert!(K < 64, "GolombRice requires K < 64");
+}
+
+impl<const K: u8> BitCode for GolombRice<K> {
+    #[inline] fn bits(value: u64) -> u64 {
+        const { Self::VALID_K };
+        (value >> K) + K as u64 + 1
+    }
+
+    fn write_code<W: io::Write>(output: &mut BitWriter<W>, value: u64) -> io::Result<()> {
+        const { Self::VALID_K };
+        output.write_unary(value >> K)?;
+        output.write_bits(value, K)
+    }
+
+    fn read_code<R: io::Read>(input: &mut BitReader<R>) -> io::Result<u64> {
+        const { Self::VALID_K };
+        let quotient = input.read_unary()?;
+        if quotient > u64::MAX >> K { return Err(io::ErrorKind::InvalidData.into()); }
+        Ok(quotient << K | input.read_bits(K)?)
+    }
+}
+
+/// Zeta code with parameter `K` (from 1 to 64, which is checked at compile time) of `value + 1`, which is from the range *[2^(hK), 2^((h+1)K))*:
+/// unary code of *h* followed by the minimal binary code of `value + 1 - 2^(hK)`.
+/// It is designed for integers with power-law distribution. Zeta code with `K=1` is the same as [`EliasGamma`].
+///
+/// See: P. Boldi, S. Vigna, *Codes for the World Wide Web*, Internet Mathematics 2(4) (2005), 407–429.
+/// <https://doi.org/10.1080/15427951.2005.10129113>
+#[derive(Clone, Copy)]
+pub struct Zeta<const K: u8>;
+
+impl<const K: u8> Zeta<K> {
+    /// Fails at compile time if `K` is not in range [1, 64].
+    const VALID_K: () = assert!(K >= 1 && K <= 64, "Zeta requires K in range [1, 64]");
+
+    /// Returns *h* and *2^(hK)* for `value + 1`.
+    #[inline] fn split(value: u64) -> (u32, u128) {
+        const { Self::VALID_K };
+        let h = (bit_len(value) - 1) / K as u32;
+        (h, 1u128 << (h * K as u32))
+    }
+}
+
+impl<const K: u8> BitCode for Zeta<K> {
+    fn bits(value: u64) -> u64 {
+        let (h, low) = Self::split(value);
+        let long = value as u128 + 1 - low >= low;
+        (h + (h + 1) * K as u32) as u64 + long as u64
+    }
+
+    fn write_code<W: io::Write>(output: &mut BitWriter<W>, value: u64) -> io::Result<()> {
+        let (h, low) = Self::split(value);
+        output.write_unary(h as u64)?;
+        let len = (h + 1) * K as u32 - 1;
+        let z = value as u128 + 1 - low;
+        if z < low {
+            write_wide(output, z, len)
+        } else {    // the most significant bits are written first, to make the code prefix-free
+            let code = z + low;
+            write_wide(output, code >> 1, len)?;
+            output.write_bit(code & 1 != 0)
+        }
+    }
+
+    fn read_code<R: io::Read>(input: &mut BitReader<R>) -> io::Result<u64> {
+        const { Self::VALID_K };
+        let h = input.read_unary()?;
+        if h * K as u64 > 64 { return Err(io::ErrorKind::InvalidData.into()); }
+        let h = h as u32;
+        let low = 1u128 << (h * K as u32);
+        let prefix = read_wide(input, (h + 1) * K as u32 - 1)?;
+        let z = if prefix < low { prefix } else { (prefix << 1 | input.read_bit()? as u128) - low };
+        to_u64(z + low - 1)
+    }
+}
+
+/// Implements [`Serializer`] for given type using its [`BitCode`].
+macro_rules! impl_bit_code_serializer {
+    ($inttype:ty) => {
+        impl<C: BitCode> Serializer<$inttype> for C {
+            #[inline] fn size(val: $inttype) -> usize { C::bits(val as u64).div_ceil(8) as usize }
+
+            fn write<W: io::Write + ?Sized>(output: &mut W, val: $inttype) -> io::Result<()> {
+                let mut output = BitWriter::new(output);
+                C::write_code(&mut output, val as u64)?;
+                output.align()
+            }
+
+            fn read<R: io::Read + ?Sized>(input: &mut R) -> io::Result<$inttype> {
+                C::read_code(&mut BitReader::new(input))?.try_into().map_err(|_| io::ErrorKind::InvalidData.into())
+            }
+
+            fn write_all_values<W, InIter>(output: &mut W, values: InIter) -> io::Result<()>
+                where W: io::Write + ?Sized, InIter: IntoIterator<Item = $inttype>
+            {
+                let mut output = BitWriter::new(output);
+                values.into_iter().try_for_each(|val| C::write_code(&mut output, val as u64))?;
+                output.align()
+            }
+
+            fn array_content_size(array: &[$inttype]) -> usize {
+                array.iter().map(|val| C::bits(*val as u64)).sum::<u64>().div_ceil(8) as usize
+            }
+
+            fn read_n<R: io::Read + ?Sized>(input: &mut R, n: usize) -> io::Result<Box<[$inttype]>> {
+                let mut input = BitReader::new(input);
+                (0..n).map(|_| C::read_code(&mut input)?.try_into().map_err(|_| io::ErrorKind::InvalidData.into())).collect()
+            }
+        }
+    };
+}
+
+impl_bit_code_serializer!(u32);
+impl_bit_code_serializer!(u64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_code<C: BitCode>(values: &[u64]) {
+        for value in values {
+            let mut output = BitWriter::new(Vec::new());
+            C::write_code(&mut output, *value).unwrap();
+            assert_eq!(output.pending_bits() as u64, C::bits(*value) % 64, "bits({})", value);
+            let buff = output.into_inner().unwrap();
+            assert_eq!(C::size(*value), buff.len());
+            assert_eq!(<C as Serializer<u64>>::read(&mut &buff[..]).unwrap(), *value);
+        }
+        let mut buff = Vec::new();
+        C::write_array(&mut buff, values).unwrap();
+        assert_eq!(buff.len(), C::array_size(values));
+        assert_eq!(<C as Serializer<u64>>::read_array(&mut &buff[..]).unwrap().as_ref(), values);
+        let small: Vec<u32> = values.iter().filter_map(|v| (*v).try_into().ok()).collect();
+        let mut buff = Vec::new();
+        C::write_array(&mut buff, &small).unwrap();
+        assert_eq!(buff.len(), C::array_size(&small));
+        assert_eq!(<C as Serializer<u32>>::read_array(&mut &buff[..]).unwrap().as_ref(), small);
+    }
+
+    const VALUES: [u64; 16] = [0, 1, 2, 3, 4, 7, 8, 100, 255, 256, 1000, 65535, 1 << 40, u64::MAX >> 1, u64::MAX - 1, u64::MAX];
+
+    #[test]
+    fn elias_gamma() {
+        assert_eq!(EliasGamma::bits(0), 1);
+        assert_eq!(EliasGamma::bits(1), 3);
+        assert_eq!(EliasGamma::bits(6), 5);
+        assert_eq!(EliasGamma::bits(u64::MAX), 129);
+        test_code::<EliasGamma>(&VALUES);
+    }
+
+    #[test]
+    fn elias_delta() {
+        assert_eq!(EliasDelta::bits(0), 1);
+        assert_eq!(EliasDelta::bits(1), 4);
+        assert_eq!(EliasDelta::bits(15), 9);
+        test_code::<EliasDelta>(&VALUES);
+    }
+
+    #[test]
+    fn golomb_rice() {
+        assert_eq!(GolombRice::<3>::bits(20), 2 + 1 + 3);
+        test_code::<GolombRice<0>>(&VALUES[..12]);
+        test_code::<GolombRice<5>>(&VALUES[..12]);
+        test_code::<GolombRice<40>>(&VALUES[..13]);
+        test_code::<GolombRice<63>>(&VALUES);
+    }
+
+    #[test]
+    fn zeta() {
+        for value in VALUES { assert_eq!(Zeta::<1>::bits(value), EliasGamma::bits(value)); }
+        assert_eq!(Zeta::<3>::bits(0), 3);
+        assert_eq!(Zeta::<3>::bits(7), 7);
+        test_code::<Zeta<1>>(&VALUES);
+        test_code::<Zeta<2>>(&VALUES);
+        test_code::<Zeta<3>>(&VALUES);
+        test_code::<Zeta<7>>(&VALUES);
+        test_code::<Zeta<64>>(&VALUES);
+    }
+
+    #[test]
+    fn packed_array() {
+        let values: Vec<u64> = (0..1000).map(|i| i % 10).collect();
+        assert!(EliasGamma::array_content_size(&values) < 1000 * 7 / 8);
+        let values: Vec<u32> = (0..1000).map(|i| i % 10).collect();
+        assert_eq!(<GolombRice<2>>::array_content_size(&values),
+                   values.iter().map(|v| (v >> 2) as usize + 3).sum::<usize>().div_ceil(8));
+    }
+}
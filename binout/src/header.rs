@@ -0,0 +1,104 @@
+use std::io;
+
+/// Header written at the beginning of a serialized structure, which allows to reject
+/// the inputs that contain other structures or are written in incompatible formats.
+///
+/// It consists of (in this order):
+/// - `magic` bytes that identify the crate (or file format) that wrote the structure,
+/// - structure `kind`, which distinguishes different structures written by the same crate,
+/// - format `version` of the structure, which should be incremented whenever its serialization changes,
+/// - `hasher_id` that identifies the hash function used by the structure
+///   (or `0` if not applicable or unknown, in which case it is not [checked](Header::check)).
+///
+/// The header occupies [`Header::SIZE`] = 8 bytes, so it does not spoil the alignment of the 64-bit words written after it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Header {
+    pub magic: [u8; 4],
+    pub kind: u8,
+    pub version: u16,
+    pub hasher_id: u8
+}
+
+/// Returns printable representation of the `magic` bytes.
+fn magic_str(magic: &[u8; 4]) -> String {
+    magic.iter().flat_map(|b| std::ascii::escape_default(*b)).map(char::from).collect()
+}
+
+impl Header {
+    /// Number of bytes occupied by the serialized header.
+    pub const SIZE: usize = 8;
+
+    /// Constructs the header with the given content.
+    #[inline] pub const fn new(magic: [u8; 4], kind: u8, version: u16, hasher_id: u8) -> Self {
+        Self { magic, kind, version, hasher_id }
+    }
+
+    /// Writes `self` to the `output`.
+    pub fn write<W: io::Write + ?Sized>(&self, output: &mut W) -> io::Result<()> {
+        let mut buff = [0u8; Self::SIZE];
+        buff[0..4].copy_from_slice(&self.magic);
+        buff[4] = self.kind;
+        buff[5..7].copy_from_slice(&self.version.to_le_bytes());
+        buff[7] = self.hasher_id;
+        output.write_all(&buff)
+    }
+
+    /// Reads header from the `input`.
+    pub fn read<R: io::Read + ?Sized>(input: &mut R) -> io::Result<Self> {
+        let mut buff = [0u8; Self::SIZE];
+        input.read_exact(&mut buff)?;
+        Ok(Self {
+            magic: [buff[0], buff[1], buff[2], buff[3]],
+            kind: buff[4],
+            version: u16::from_le_bytes([buff[5], buff[6]]),
+            hasher_id: buff[7]
+        })
+    }
+
+    /// Returns [`io::ErrorKind::InvalidData`] error that describes the first difference
+    /// between `self` (the expected header) and the `found` one, or `Ok` if they are equal.
+    /// Hasher identifiers are compared only if both are non-zero.
+    pub fn check(&self, found: &Self) -> io::Result<()> {
+        let message = if self.magic != found.magic {
+            format!("invalid magic bytes \"{}\" (expected \"{}\"), the input does not contain the expected structure",
+                magic_str(&found.magic), magic_str(&self.magic))
+        } else if self.kind != found.kind {
+            format!("the input contains the structure of kind {} instead of expected kind {}", found.kind, self.kind)
+        } else if self.version != found.version {
+            format!("unsupported format version {} (only version {} is supported)", found.version, self.version)
+        } else if self.hasher_id != 0 && found.hasher_id != 0 && self.hasher_id != found.hasher_id {
+            format!("the structure was written using the hasher with id {}, but it is read using the hasher with id {}",
+                found.hasher_id, self.hasher_id)
+        } else {
+            return Ok(());
+        };
+        Err(io::Error::new(io::ErrorKind::InvalidData, message))
+    }
+
+    /// Reads header from the `input` and [checks](Header::check) if it is equal to `self`.
+    #[inline] pub fn read_expected<R: io::Read + ?Sized>(&self, input: &mut R) -> io::Result<()> {
+        self.check(&Self::read(input)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_read_check() {
+        let header = Header::new(*b"test", 3, 258, 7);
+        let mut buff = Vec::new();
+        header.write(&mut buff).unwrap();
+        assert_eq!(buff.len(), Header::SIZE);
+        assert_eq!(Header::read(&mut &buff[..]).unwrap(), header);
+        assert!(header.read_expected(&mut &buff[..]).is_ok());
+        for other in [Header::new(*b"tEst", 3, 258, 7), Header::new(*b"test", 4, 258, 7),
+                      Header::new(*b"test", 3, 1, 7), Header::new(*b"test", 3, 258, 8)] {
+            let err = other.read_expected(&mut &buff[..]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+        assert!(Header::new(*b"test", 3, 258, 0).read_expected(&mut &buff[..]).is_ok());
+        assert_eq!(header.read_expected(&mut &buff[..7]).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
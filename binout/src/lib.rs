@@ -1,7 +1,21 @@
 #![doc = include_str!("../README.md")]
 
+mod bits;
+pub use bits::{BitWriter, BitReader};
+
+mod codes;
+pub use codes::{BitCode, EliasGamma, EliasDelta, GolombRice, Zeta};
+
+mod stream_vbyte;
+pub use stream_vbyte::StreamVByte;
+
+mod header;
+pub use header::Header;
+
 /// Trait implemented by each serializer for the following types:
-/// `u8`, `u16`, `u32`, `u64`, `usize` (which, for portability, is always serialized the same as `u64`).
+/// `u8`, `u16`, `u32`, `u64`, `usize` (which, for portability, is always serialized the same as `u64`)
+/// and (by some serializers) their signed counterparts (`isize` is always serialized the same as `i64`)
+/// and floating-point types `f32` and `f64`.
 pub trait Serializer<T: Copy>: Copy {
 
     /// Either size of each value in bytes (if each value occupies constant size) or `None`.
@@ -62,6 +76,25 @@ pub trait Serializer<T: Copy>: Copy {
         let n = VByte::read(input)?;
         Self::read_n(input, n)
     }
+
+    /// Deserialize value from the beginning of the `input` slice. Returns the value and the rest of the `input`.
+    #[inline] fn read_from_slice(mut input: &[u8]) -> std::io::Result<(T, &[u8])> {
+        let value = Self::read(&mut input)?;
+        Ok((value, input))
+    }
+
+    /// Deserialize `n` values from the beginning of the `input` slice. Returns the values and the rest of the `input`.
+    #[inline] fn read_n_from_slice(mut input: &[u8], n: usize) -> std::io::Result<(Box<[T]>, &[u8])> {
+        let values = Self::read_n(&mut input, n)?;
+        Ok((values, input))
+    }
+
+    /// Deserialize array (see [`read_array`](Serializer::read_array)) from the beginning of the `input` slice.
+    /// Returns the array and the rest of the `input`.
+    #[inline] fn read_array_from_slice(mut input: &[u8]) -> std::io::Result<(Box<[T]>, &[u8])> {
+        let values = Self::read_array(&mut input)?;
+        Ok((values, input))
+    }
 }
 
 impl<S> Serializer<usize> for S where S: Serializer<u64> {
@@ -78,7 +111,22 @@ impl<S> Serializer<usize> for S where S: Serializer<u64> {
     }
 }
 
+impl<S> Serializer<isize> for S where S: Serializer<i64> {
+    const CONST_SIZE: Option<usize> = S::CONST_SIZE;
+
+    #[inline] fn size(val: isize) -> usize { S::size(val as i64) }
+
+    #[inline] fn write<W: std::io::Write + ?Sized>(output: &mut W, val: isize) -> std::io::Result<()> {
+        S::write(output, val as i64)
+    }
+    
+    #[inline] fn read<R: std::io::Read + ?Sized>(input: &mut R) -> std::io::Result<isize> {
+        S::read(input).map(|v| v as isize)
+    }
+}
+
 /// Serialize values as-is, in little-endian bytes order.
+/// Supports all primitive integer types, including `u128` and `i128`, as well as `f32` and `f64`.
 #[derive(Clone, Copy)]
 pub struct AsIs;
 
@@ -108,7 +156,51 @@ macro_rules! impl_le_serializer {
     )
 }
 
-impl_le_serializer!(AsIs, u8, u16, u32, u64);  // , i8, i16, i32, i64, i128, isize
+impl_le_serializer!(AsIs, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+mod private { pub trait Sealed {} }
+
+/// Primitive integer and floating-point types whose arrays serialized by [`AsIs`] can be borrowed directly from bytes
+/// (see [`AsIs::read_n_borrowed`]).
+pub trait Plain: private::Sealed + Copy {}
+
+macro_rules! impl_plain {
+    ($($t:ty),+) => { $( impl private::Sealed for $t {} impl Plain for $t {} )+ };
+}
+
+impl_plain!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl AsIs {
+    /// Deserialize `n` values from the beginning of the `input` slice without copying them, if possible.
+    /// Returns the values and the rest of the `input`.
+    ///
+    /// The values are borrowed from the `input` if the target is little-endian and
+    /// the values are properly aligned in memory (which is the case, for example,
+    /// for 8-byte aligned `u64` arrays in a memory-mapped file). Otherwise, they are copied.
+    pub fn read_n_borrowed<T: Plain>(input: &[u8], n: usize) -> std::io::Result<(std::borrow::Cow<'_, [T]>, &[u8])>
+        where AsIs: Serializer<T>
+    {
+        let bytes = n.checked_mul(std::mem::size_of::<T>()).filter(|bytes| *bytes <= input.len())
+            .ok_or(std::io::ErrorKind::UnexpectedEof)?;
+        let (mut content, rest) = input.split_at(bytes);
+        if cfg!(target_endian = "little") {
+            // SAFETY: every bit pattern is a valid value of the primitive numeric type T
+            let (prefix, values, _) = unsafe { content.align_to::<T>() };
+            if prefix.is_empty() { return Ok((std::borrow::Cow::Borrowed(values), rest)); }
+        }
+        Ok((std::borrow::Cow::Owned(Self::read_n(&mut content, n)?.into_vec()), rest))
+    }
+
+    /// Deserialize array (whose size is serialized in [`VByte`] format) from the beginning of the `input` slice
+    /// without copying its values, if possible (see [`AsIs::read_n_borrowed`]).
+    /// Returns the array and the rest of the `input`.
+    pub fn read_array_borrowed<T: Plain>(input: &[u8]) -> std::io::Result<(std::borrow::Cow<'_, [T]>, &[u8])>
+        where AsIs: Serializer<T>
+    {
+        let (n, input) = <VByte as Serializer<usize>>::read_from_slice(input)?;
+        Self::read_n_borrowed(input, n)
+    }
+}
 
 /// Returns byte whose 7 least significant bits are copied from `v` and the most significant bit is `1`.
 macro_rules! m { ($v:expr) => { $v as u8 | (1 << 7) } }
@@ -137,10 +229,38 @@ fn vbyte_read<R: std::io::Read + ?Sized>(input: &mut R, max_shift: u8) -> std::i
 /// - For `u64` values below $2^63$, the encoding is identical to the classic *VByte*/*LEB128*;
 ///   For larger values, the encoding always stores the most significant byte of value as is, using a total of 9 bytes,
 ///   whereas a classic VByte could use 10 bytes.
+///
+/// Signed values (`i16`, `i32`, `i64`) are first mapped to unsigned ones by zig-zag encoding
+/// (0, -1, 1, -2, 2, ... are mapped to 0, 1, 2, 3, 4, ...), so the values of small magnitude occupy few bytes.
+/// `i8` values are always stored as is, using 1 byte.
 #[derive(Clone, Copy)]
 pub struct VByte;
 
-impl_le_serializer!(VByte, u8);
+impl_le_serializer!(VByte, u8, i8);
+
+// Implement serializer of signed values that zig-zag encodes them and serialize using unsigned serializer.
+macro_rules! impl_zigzag_serializer {
+    ($sertype:ty, $signed:ty, $unsigned:ty) => {
+        impl Serializer<$signed> for $sertype {
+            #[inline] fn size(val: $signed) -> usize {
+                <Self as Serializer<$unsigned>>::size(((val << 1) ^ (val >> (<$signed>::BITS - 1))) as $unsigned)
+            }
+
+            #[inline] fn write<W: ::std::io::Write + ?Sized>(output: &mut W, val: $signed) -> ::std::io::Result<()> {
+                <Self as Serializer<$unsigned>>::write(output, ((val << 1) ^ (val >> (<$signed>::BITS - 1))) as $unsigned)
+            }
+
+            #[inline] fn read<R: ::std::io::Read + ?Sized>(input: &mut R) -> ::std::io::Result<$signed> {
+                let val = <Self as Serializer<$unsigned>>::read(input)?;
+                Ok((val >> 1) as $signed ^ -((val & 1) as $signed))
+            }
+        }
+    };
+}
+
+impl_zigzag_serializer!(VByte, i16, u16);
+impl_zigzag_serializer!(VByte, i32, u32);
+impl_zigzag_serializer!(VByte, i64, u64);
 
 impl Serializer<u16> for VByte {
     fn size(val: u16) -> usize {
@@ -287,6 +407,83 @@ mod tests {
         test_vbyte(u32::MAX);
     }
 
+    #[test] fn asis_all_types() {
+        test_serializer::<u8, AsIs>(200);
+        test_serializer::<u128, AsIs>(u128::MAX - 5);
+        test_serializer::<i8, AsIs>(-100);
+        test_serializer::<i16, AsIs>(i16::MIN);
+        test_serializer::<i32, AsIs>(-123456);
+        test_serializer::<i64, AsIs>(i64::MAX);
+        test_serializer::<i128, AsIs>(i128::MIN + 1);
+        test_serializer::<isize, AsIs>(-1);
+        test_serializer_array::<i32, AsIs>(&[0, -1, 1, i32::MIN, i32::MAX]);
+        test_serializer_array::<u128, AsIs>(&[0, 1 << 100, u128::MAX]);
+    }
+
+    #[test] fn asis_floats() {
+        test_serializer::<f32, AsIs>(-1.5);
+        test_serializer::<f64, AsIs>(f64::MIN_POSITIVE);
+        test_serializer_array::<f32, AsIs>(&[0.0, -0.0, f32::INFINITY, f32::MAX]);
+        test_serializer_array::<f64, AsIs>(&[1e300, -2.25, f64::NEG_INFINITY]);
+        let mut buff = Vec::new();
+        AsIs::write(&mut buff, f64::NAN).unwrap();
+        assert_eq!(buff, f64::NAN.to_le_bytes());
+        assert!(<AsIs as Serializer<f64>>::read(&mut &buff[..]).unwrap().is_nan());
+    }
+
+    #[test] fn vbyte_signed() {
+        for (value, size) in [(0, 1), (-1, 1), (1, 1), (-64, 1), (63, 1), (64, 2), (-65, 2), (i16::MIN, 3), (i16::MAX, 3)] {
+            test_serializer::<i16, VByte>(value);
+            assert_eq!(VByte::size(value), size);
+        }
+        for value in [0, -1, 1, -1000, 1000, i32::MIN, i32::MAX, i32::MIN + 1] {
+            test_serializer::<i32, VByte>(value);
+        }
+        for value in [0, -1, 1, -1 << 40, 1 << 40, i64::MIN, i64::MAX, i64::MIN + 1] {
+            test_serializer::<i64, VByte>(value);
+        }
+        test_serializer::<isize, VByte>(-7);
+        test_serializer::<i8, VByte>(i8::MIN);
+        test_serializer_array::<i64, VByte>(&[-3, 5, 0, i64::MIN, -200]);
+        assert_eq!(<VByte as Serializer<i64>>::array_content_size(&[-3, 5, 0, -64, 64]), 6);
+    }
+
+    #[test] fn from_slice() {
+        let mut buff = Vec::new();
+        VByte::write(&mut buff, 300u32).unwrap();
+        VByte::write_array(&mut buff, &[1u64, 1000, u64::MAX]).unwrap();
+        AsIs::write(&mut buff, -5i16).unwrap();
+        let (value, rest) = <VByte as Serializer<u32>>::read_from_slice(&buff).unwrap();
+        assert_eq!(value, 300);
+        let (array, rest) = <VByte as Serializer<u64>>::read_array_from_slice(rest).unwrap();
+        assert_eq!(array.as_ref(), [1, 1000, u64::MAX]);
+        let (value, rest) = <AsIs as Serializer<i16>>::read_from_slice(rest).unwrap();
+        assert_eq!(value, -5);
+        assert!(rest.is_empty());
+        assert!(<AsIs as Serializer<u32>>::read_from_slice(rest).is_err());
+    }
+
+    #[test] fn borrowed() {
+        let values = [1u64, 2, u64::MAX, 1 << 40];
+        let mut words = vec![0u64; 6];   // u64 words guarantee the alignment of the bytes
+        let bytes = unsafe { std::slice::from_raw_parts_mut(words.as_mut_ptr() as *mut u8, 48) };
+        AsIs::write_all(&mut &mut bytes[8..], &values).unwrap();
+        let (read, rest) = AsIs::read_n_borrowed::<u64>(&bytes[8..], 4).unwrap();
+        assert_eq!(read.as_ref(), values);
+        assert_eq!(matches!(read, std::borrow::Cow::Borrowed(_)), cfg!(target_endian = "little"));
+        assert_eq!(rest.len(), 8);
+        AsIs::write_all(&mut &mut bytes[1..], &values).unwrap();
+        let (read, _) = AsIs::read_n_borrowed::<u64>(&bytes[1..], 4).unwrap();
+        assert_eq!(read.as_ref(), values);
+        assert!(matches!(read, std::borrow::Cow::Owned(_)));
+        assert!(AsIs::read_n_borrowed::<u64>(&bytes[1..], 6).is_err());
+        let mut buff = Vec::new();
+        AsIs::write_array(&mut buff, &[7u8, 8, 9]).unwrap();
+        let (read, rest) = AsIs::read_array_borrowed::<u8>(&buff).unwrap();
+        assert_eq!(read.as_ref(), [7, 8, 9]);
+        assert!(rest.is_empty());
+    }
+
     #[test] fn vbyte_u64() {
         let test_vbyte = test_serializer::<u64, VByte>;
         test_vbyte(0);
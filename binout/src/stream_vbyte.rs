@@ -0,0 +1,320 @@
+use std::io;
+use crate::Serializer;
+
+/// Serializer that uses *Stream VByte* format, designed for fast (SIMD) decoding of arrays.
+///
+/// Each `u32` value is stored using 1 to 4 bytes (in little-endian order), and its length is stored in a separate,
+/// 2-bit control field. Arrays are stored as all control fields (4 per byte), followed by all data bytes.
+/// Thanks to this, 4 values can be decoded by a single shuffle instruction, which is used
+/// on x86-64 CPUs supporting SSSE3 and on AArch64 (NEON) CPUs. On other CPUs, a (branchless) scalar decoder is used.
+///
+/// `u64` values are stored analogously, using 1 to 8 bytes and 4-bit control fields (2 per byte),
+/// and are decoded by the scalar decoder.
+///
+/// Single values (not arrays) are stored as a control byte followed by the data bytes.
+///
+/// See: D. Lemire, N. Kurz, C. Rupp, *Stream VByte: Faster byte-oriented integer compression*,
+/// Information Processing Letters 130 (2018), 1–6. <https://doi.org/10.1016/j.ipl.2017.09.011>
+#[derive(Clone, Copy)]
+pub struct StreamVByte;
+
+/// Number of extra bytes after the data, that allow the decoders to read whole 16-byte blocks.
+const PADDING: usize = 16;
+
+/// Returns the number of bytes (1 to 4) needed to store `value`.
+#[inline] fn u32_len(value: u32) -> usize {
+    (4 - value.leading_zeros() as usize / 8).max(1)
+}
+
+/// Returns the number of bytes (1 to 8) needed to store `value`.
+#[inline] fn u64_len(value: u64) -> usize {
+    (8 - value.leading_zeros() as usize / 8).max(1)
+}
+
+/// Returns the total length of the data of 4 `u32` values whose lengths are stored in the `control` byte.
+const fn control_len(control: u8) -> usize {
+    (control & 3) as usize + ((control >> 2) & 3) as usize + ((control >> 4) & 3) as usize + (control >> 6) as usize + 4
+}
+
+/// Returns the shuffle mask that decodes 4 `u32` values whose lengths are stored in the `control` byte.
+/// Indices of bytes that should be zeroed are `0xFF`.
+const fn shuffle_mask(control: u8) -> [u8; 16] {
+    let mut result = [0xFF; 16];
+    let mut source = 0;
+    let mut value = 0;
+    while value < 4 {
+        let len = ((control >> (2 * value)) & 3) as usize + 1;
+        let mut byte = 0;
+        while byte < len {
+            result[value * 4 + byte] = source;
+            source += 1;
+            byte += 1;
+        }
+        value += 1;
+    }
+    result
+}
+
+/// Returns the shuffle masks for all control bytes.
+const fn shuffle_table() -> [[u8; 16]; 256] {
+    let mut result = [[0; 16]; 256];
+    let mut control = 0;
+    while control < 256 {
+        result[control] = shuffle_mask(control as u8);
+        control += 1;
+    }
+    result
+}
+
+/// Shuffle masks for all control bytes of `u32` arrays.
+static SHUFFLE: [[u8; 16]; 256] = shuffle_table();
+
+/// Decodes `u32` values using SSSE3 shuffle instruction.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+unsafe fn decode_quads_ssse3(control: &[u8], data: &[u8], out: &mut [u32]) {
+    use std::arch::x86_64::*;
+    let mut position = 0;
+    for (c, out) in control.iter().zip(out.chunks_exact_mut(4)) {
+        debug_assert!(position + 16 <= data.len());
+        let d = _mm_loadu_si128(data.as_ptr().add(position) as *const __m128i);
+        let m = _mm_loadu_si128(SHUFFLE[*c as usize].as_ptr() as *const __m128i);
+        _mm_storeu_si128(out.as_mut_ptr() as *mut __m128i, _mm_shuffle_epi8(d, m));
+        position += control_len(*c);
+    }
+}
+
+/// Decodes `u32` values using NEON table lookup instruction.
+#[cfg(target_arch = "aarch64")]
+unsafe fn decode_quads_neon(control: &[u8], data: &[u8], out: &mut [u32]) {
+    use std::arch::aarch64::*;
+    let mut position = 0;
+    for (c, out) in control.iter().zip(out.chunks_exact_mut(4)) {
+        debug_assert!(position + 16 <= data.len());
+        let d = vld1q_u8(data.as_ptr().add(position));
+        let m = vld1q_u8(SHUFFLE[*c as usize].as_ptr());
+        vst1q_u8(out.as_mut_ptr() as *mut u8, vqtbl1q_u8(d, m));
+        position += control_len(*c);
+    }
+}
+
+/// Decodes `u32` values using the portable, scalar decoder.
+#[cfg_attr(target_arch = "aarch64", allow(dead_code))]
+fn decode_quads_scalar(control: &[u8], data: &[u8], out: &mut [u32]) {
+    let mut position = 0;
+    for (c, out) in control.iter().zip(out.chunks_exact_mut(4)) {
+        for (i, out) in out.iter_mut().enumerate() {
+            let len = ((c >> (2 * i)) & 3) as usize + 1;
+            let word = u32::from_le_bytes(data[position..position+4].try_into().unwrap());
+            *out = word & (u32::MAX >> (32 - 8 * len));
+            position += len;
+        }
+    }
+}
+
+/// Decodes `u32` values (whose number is a multiple of 4) described by `control` bytes, from `data`
+/// (which must be followed by [`PADDING`] bytes) to `out`.
+fn decode_u32(control: &[u8], data: &[u8], out: &mut [u32]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("ssse3") {
+        unsafe { decode_quads_ssse3(control, data, out) };
+        return;
+    }
+    #[cfg(target_arch = "aarch64")]
+    unsafe { decode_quads_neon(control, data, out) };
+    #[cfg(not(target_arch = "aarch64"))]
+    decode_quads_scalar(control, data, out);
+}
+
+/// Returns the length of the data of `n` values whose lengths are described by the `control` bytes,
+/// using `control_bits` bits per value.
+/// Returns [`io::ErrorKind::InvalidData`] error if any length exceeds 8 bytes (which is possible only for `u64` control fields).
+fn data_len(control: &[u8], n: usize, control_bits: u8) -> io::Result<usize> {
+    let per_byte = 8 / control_bits as usize;
+    let mask = (1u8 << control_bits) - 1;
+    let mut result = 0;
+    for i in 0..n {
+        let len = ((control[i / per_byte] >> ((i % per_byte) as u8 * control_bits)) & mask) as usize + 1;
+        if len > 8 { return Err(io::Error::new(io::ErrorKind::InvalidData, "StreamVByte: value longer than 8 bytes")); }
+        result += len;
+    }
+    Ok(result)
+}
+
+/// Reads `control_len` control bytes and the data of `n` values described by them.
+/// The returned data are followed by [`PADDING`] zeroed bytes.
+fn read_control_and_data<R: io::Read + ?Sized>(input: &mut R, control_len: usize, n: usize, control_bits: u8) -> io::Result<(Box<[u8]>, Vec<u8>)> {
+    let mut control = vec![0; control_len].into_boxed_slice();
+    input.read_exact(&mut control)?;
+    let len = data_len(&control, n, control_bits)?;
+    let mut data = vec![0; len + PADDING];
+    input.read_exact(&mut data[..len])?;
+    Ok((control, data))
+}
+
+impl Serializer<u32> for StreamVByte {
+    #[inline] fn size(val: u32) -> usize { 1 + u32_len(val) }
+
+    fn write<W: io::Write + ?Sized>(output: &mut W, val: u32) -> io::Result<()> {
+        let len = u32_len(val);
+        output.write_all(&[len as u8 - 1])?;
+        output.write_all(&val.to_le_bytes()[..len])
+    }
+
+    fn read<R: io::Read + ?Sized>(input: &mut R) -> io::Result<u32> {
+        let mut bytes = [0u8; 4];
+        input.read_exact(&mut bytes[..1])?;
+        if bytes[0] > 3 { return Err(io::Error::new(io::ErrorKind::InvalidData, "StreamVByte: u32 value longer than 4 bytes")); }
+        let len = bytes[0] as usize + 1;
+        bytes[0] = 0;
+        input.read_exact(&mut bytes[..len])?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn write_all_values<W, InIter>(output: &mut W, values: InIter) -> io::Result<()>
+        where W: io::Write + ?Sized, InIter: IntoIterator<Item = u32>
+    {
+        let values: Vec<u32> = values.into_iter().collect();
+        let mut control = vec![0u8; values.len().div_ceil(4)];
+        let mut data = Vec::with_capacity(values.len() * 4);
+        for (i, v) in values.iter().enumerate() {
+            let len = u32_len(*v);
+            control[i / 4] |= ((len - 1) as u8) << (2 * (i % 4));
+            data.extend_from_slice(&v.to_le_bytes()[..len]);
+        }
+        output.write_all(&control)?;
+        output.write_all(&data)
+    }
+
+    fn array_content_size(array: &[u32]) -> usize {
+        array.len().div_ceil(4) + array.iter().map(|v| u32_len(*v)).sum::<usize>()
+    }
+
+    fn read_n<R: io::Read + ?Sized>(input: &mut R, n: usize) -> io::Result<Box<[u32]>> {
+        let (control, data) = read_control_and_data(input, n.div_ceil(4), n, 2)?;
+        let mut result = vec![0; control.len() * 4];
+        decode_u32(&control, &data, &mut result);
+        result.truncate(n);
+        Ok(result.into_boxed_slice())
+    }
+}
+
+impl Serializer<u64> for StreamVByte {
+    #[inline] fn size(val: u64) -> usize { 1 + u64_len(val) }
+
+    fn write<W: io::Write + ?Sized>(output: &mut W, val: u64) -> io::Result<()> {
+        let len = u64_len(val);
+        output.write_all(&[len as u8 - 1])?;
+        output.write_all(&val.to_le_bytes()[..len])
+    }
+
+    fn read<R: io::Read + ?Sized>(input: &mut R) -> io::Result<u64> {
+        let mut bytes = [0u8; 8];
+        input.read_exact(&mut bytes[..1])?;
+        if bytes[0] > 7 { return Err(io::Error::new(io::ErrorKind::InvalidData, "StreamVByte: value longer than 8 bytes")); }
+        let len = bytes[0] as usize + 1;
+        bytes[0] = 0;
+        input.read_exact(&mut bytes[..len])?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn write_all_values<W, InIter>(output: &mut W, values: InIter) -> io::Result<()>
+        where W: io::Write + ?Sized, InIter: IntoIterator<Item = u64>
+    {
+        let values: Vec<u64> = values.into_iter().collect();
+        let mut control = vec![0u8; values.len().div_ceil(2)];
+        let mut data = Vec::with_capacity(values.len() * 8);
+        for (i, v) in values.iter().enumerate() {
+            let len = u64_len(*v);
+            control[i / 2] |= ((len - 1) as u8) << (4 * (i % 2));
+            data.extend_from_slice(&v.to_le_bytes()[..len]);
+        }
+        output.write_all(&control)?;
+        output.write_all(&data)
+    }
+
+    fn array_content_size(array: &[u64]) -> usize {
+        array.len().div_ceil(2) + array.iter().map(|v| u64_len(*v)).sum::<usize>()
+    }
+
+    fn read_n<R: io::Read + ?Sized>(input: &mut R, n: usize) -> io::Result<Box<[u64]>> {
+        let (control, data) = read_control_and_data(input, n.div_ceil(2), n, 4)?;
+        let mut position = 0;
+        Ok((0..n).map(|i| {
+            let len = ((control[i / 2] >> (4 * (i % 2))) & 0xF) as usize + 1;   // at most 8, checked by data_len
+            let word = u64::from_le_bytes(data[position..position+8].try_into().unwrap());
+            position += len;
+            word & (u64::MAX >> (64 - 8 * len))
+        }).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values_u32() -> Vec<u32> {
+        let mut x = 0x2545_F491u32;
+        (0..1003).map(|i| { x ^= x << 13; x ^= x >> 17; x ^= x << 5; x >> (i % 32) }).collect()
+    }
+
+    #[test]
+    fn single_values() {
+        for v in [0, 1, 255, 256, 65535, 65536, 1 << 24, u32::MAX] {
+            let mut buff = Vec::new();
+            StreamVByte::write(&mut buff, v).unwrap();
+            assert_eq!(buff.len(), StreamVByte::size(v));
+            assert_eq!(<StreamVByte as Serializer<u32>>::read(&mut &buff[..]).unwrap(), v);
+        }
+        for v in [0, 1, 255, 256, 1 << 40, 1 << 56, u64::MAX] {
+            let mut buff = Vec::new();
+            StreamVByte::write(&mut buff, v).unwrap();
+            assert_eq!(buff.len(), StreamVByte::size(v));
+            assert_eq!(<StreamVByte as Serializer<u64>>::read(&mut &buff[..]).unwrap(), v);
+        }
+    }
+
+    #[test]
+    fn arrays_u32() {
+        let values = values_u32();
+        for n in [0, 1, 2, 3, 4, 5, 17, 1000, 1003] {
+            let mut buff = Vec::new();
+            StreamVByte::write_array(&mut buff, &values[..n]).unwrap();
+            assert_eq!(buff.len(), StreamVByte::array_size(&values[..n]));
+            assert_eq!(<StreamVByte as Serializer<u32>>::read_array(&mut &buff[..]).unwrap().as_ref(), &values[..n]);
+        }
+    }
+
+    #[test]
+    fn scalar_decoder() {
+        let values = values_u32();
+        let mut buff = Vec::new();
+        StreamVByte::write_all(&mut buff, &values[..1000]).unwrap();
+        let (control, data) = buff.split_at(250);
+        let mut data = data.to_vec();
+        data.resize(data.len() + PADDING, 0);
+        let mut result = vec![0; 1000];
+        decode_quads_scalar(control, &data, &mut result);
+        assert_eq!(result, &values[..1000]);
+    }
+
+    #[test]
+    fn arrays_u64() {
+        let values: Vec<u64> = values_u32().iter().enumerate().map(|(i, v)| (*v as u64) << (i % 33)).collect();
+        for n in [0, 1, 2, 3, 1000, 1003] {
+            let mut buff = Vec::new();
+            StreamVByte::write_array(&mut buff, &values[..n]).unwrap();
+            assert_eq!(buff.len(), StreamVByte::array_size(&values[..n]));
+            assert_eq!(<StreamVByte as Serializer<u64>>::read_array(&mut &buff[..]).unwrap().as_ref(), &values[..n]);
+        }
+    }
+
+    #[test]
+    fn invalid_u64_control() {
+        let mut buff = Vec::new();
+        StreamVByte::write_all(&mut buff, &[1u64, 2, 3]).unwrap();
+        buff[0] |= 0x80;    // length of the second value: 9 bytes
+        assert_eq!(<StreamVByte as Serializer<u64>>::read_n(&mut &buff[..], 3).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(<StreamVByte as Serializer<u64>>::read(&mut &[8u8, 0, 0, 0, 0, 0, 0, 0, 0][..]).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,16 +1,31 @@
+use std::io;
+use std::ops::Deref;
+use binout::{AsIs, Serializer};
 use super::{ceiling_div, n_lowest_bits};
-use dyn_size_of::GetSize;
+use super::select::{BitArrayWithRankSelect, select_in_block, last_satisfying, hinted_blocks, push_hints, next_with_rank, prev_with_rank};
+use dyn_size_of::{GetSize, SizeReport};
 
 /// The trait implemented by the types which holds the array of bits and the rank structure for this array.
 /// Thanks to the rank structure, the implementor can quickly return the number of ones
 /// in requested number of the first bits of the stored array (see `rank` method).
-pub trait BitArrayWithRank {
+///
+/// Unlike [`BitArrayWithRank`], it does not require the implementor to be constructible from owned content,
+/// so it is implemented also by the structures that wrap borrowed (e.g. memory-mapped) words.
+pub trait Rank {
+    /// Returns the number of ones in first `index` bits of the `content`.
+    fn rank(&self, index: usize) -> u64;
+
+    /// Returns the bit of the `content` with given `index`.
+    #[inline] fn get_bit(&self, index: usize) -> bool {
+        self.rank(index + 1) != self.rank(index)
+    }
+}
+
+/// The trait implemented by the [`Rank`] structures which can be built for the given array of bits.
+pub trait BitArrayWithRank: Rank {
     /// Returns `Self` (that stores `content` and the rank structure) and
     /// the number of bits set in the whole `content`.
     fn build(content: Box<[u64]>) -> (Self, u64) where Self: Sized;
-
-    /// Returns the number of ones in first `index` bits of the `content`.
-    fn rank(&self, index: usize) -> u64;
 }
 
 /// Returns number of bits set (to one) in `content`.
@@ -18,63 +33,215 @@ pub trait BitArrayWithRank {
     content.iter().map(|v| v.count_ones() as u64).sum()
 }
 
+/// Splits `words` into the first `len` words and the rest. Returns an error if `words` are too short.
+fn split_words(words: &[u64], len: u64) -> io::Result<(&[u64], &[u64])> {
+    if len > words.len() as u64 { return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "too few words to wrap")); }
+    Ok(words.split_at(len as usize))
+}
+
+/// Returns whether `hints` are non-decreasing indices of blocks, each less than `blocks`.
+fn hints_are_valid<H: Copy + Into<u64>>(hints: &[H], blocks: usize) -> bool {
+    hints.windows(2).all(|w| w[0].into() <= w[1].into()) && hints.last().is_none_or(|h| (*h).into() < blocks as u64)
+}
+
+mod private { pub trait Sealed {} }
+
+/// Unsigned integer type (`u32` or `u64`) used by [`ArrayWithRankSimple`] to store ranks and select hints.
+pub trait RankCounter: private::Sealed + Copy + Into<u64> + TryFrom<u64> + From<u32>
+    + std::ops::Add<Output = Self> + GetSize
+{
+    /// Writes `values` to `output`, padded by zeros to the multiple of 64 bits.
+    fn write_padded(output: &mut dyn io::Write, values: &[Self]) -> io::Result<()>;
+
+    /// Reads `len` values (padded to the multiple of 64 bits) from `input`.
+    fn read_padded(input: &mut dyn io::Read, len: usize) -> io::Result<Box<[Self]>>;
+
+    /// Returns the number of 64-bit words occupied by `len` values (padded to the multiple of 64 bits).
+    #[inline] fn padded_words(len: usize) -> usize {
+        ceiling_div(len * std::mem::size_of::<Self>(), 8)
+    }
+}
+
+impl private::Sealed for u32 {}
+
+impl RankCounter for u32 {
+    fn write_padded(output: &mut dyn io::Write, values: &[Self]) -> io::Result<()> {
+        AsIs::write_all(output, values.iter())?;
+        if values.len() % 2 == 1 { AsIs::write(output, 0u32)?; }
+        Ok(())
+    }
+
+    fn read_padded(input: &mut dyn io::Read, len: usize) -> io::Result<Box<[Self]>> {
+        let result = AsIs::read_n(input, len)?;
+        if len % 2 == 1 { <AsIs as Serializer<u32>>::read(input)?; }
+        Ok(result)
+    }
+}
+
+impl private::Sealed for u64 {}
+
+impl RankCounter for u64 {
+    #[inline] fn write_padded(output: &mut dyn io::Write, values: &[Self]) -> io::Result<()> {
+        AsIs::write_all(output, values.iter())
+    }
+
+    #[inline] fn read_padded(input: &mut dyn io::Read, len: usize) -> io::Result<Box<[Self]>> {
+        AsIs::read_n(input, len)
+    }
+}
+
+/// Splits `words` into the first `len` values of type `C` (padded to whole words) and the remaining words.
+/// Returns an error if `words` are too short.
+#[cfg(target_endian = "little")]
+fn split_counters<C: RankCounter>(words: &[u64], len: u64) -> io::Result<(&[C], &[u64])> {
+    let (head, rest) = split_words(words, C::padded_words(len as usize) as u64)?;
+    // SAFETY: C is u32 or u64 (RankCounter is sealed), whose alignment is not greater than alignment of u64,
+    // and head contains at least len values of type C
+    Ok((unsafe { std::slice::from_raw_parts(head.as_ptr() as *const C, len as usize) }, rest))
+}
+
 /// The structure that holds array of bits `content` and `ranks` structure that takes no more than 3.125% extra space.
 /// It can returns the number of ones in first `index` bits of the `content` (see `rank` method) in *O(1)* time.
+/// Additionally, it holds sampled select hints which allow to quickly find positions of ones and zeros
+/// of given ranks (see `select1` and `select0` methods).
+/// Select is not constant-time: it binary searches the l2 blocks (of 2048 bits) between two subsequent hints,
+/// so it takes *O(log(b))* time, where *b* is the number of blocks spanned by 8192 subsequent ones (zeros).
+/// This is *O(1)* if the density of ones (zeros) is bounded from below, but *O(log n)* in the worst case.
 ///
 /// It uses modified version of the structure described in the paper:
 /// - Zhou D., Andersen D.G., Kaminsky M. (2013) "Space-Efficient, High-Performance Rank and Select Structures on Uncompressed Bit Sequences".
 ///   In: Bonifaci V., Demetrescu C., Marchetti-Spaccamela A. (eds) Experimental Algorithms. SEA 2013.
 ///   Lecture Notes in Computer Science, vol 7933. Springer, Berlin, Heidelberg. <https://doi.org/10.1007/978-3-642-38527-8_15>
+///
+/// The content and ranks are stored in `S`, which can be any type that dereferences to `[u64]`,
+/// and the select hints are stored in `H`, which can be any type that dereferences to `[u32]`.
+/// By default, they are owned, but they can be also, for example, borrowed from a memory-mapped file
+/// (see [`ArrayWithRank101111::write`] and [`ArrayWithRank101111::from_words`]).
 #[derive(Clone)]
-pub struct ArrayWithRank101111 {
-    pub content: Box<[u64]>,  // BitVec
-    pub l1ranks: Box<[u64]>,  // Each cell holds one rank using 64 bits
-    pub l2ranks: Box<[u64]>   // Each cell holds 4 ranks using [bits]: 32 (absolute), and, in reverse order (deltas): 10, 11, 11.
+pub struct ArrayWithRank101111<S = Box<[u64]>, H = Box<[u32]>> {
+    pub content: S,  // BitVec
+    pub l1ranks: S,  // Each cell holds one rank using 64 bits
+    pub l2ranks: S,  // Each cell holds 4 ranks using [bits]: 32 (absolute), and, in reverse order (deltas): 10, 11, 11.
+    pub select1_hints: H,  // Indices of l2 blocks that contain the ones with ranks 0, 8192, 2*8192, ...
+    pub select0_hints: H   // Indices of l2 blocks that contain the zeros with ranks 0, 8192, 2*8192, ...
 }
 
-impl GetSize for ArrayWithRank101111 {
+impl<S: GetSize, H: GetSize> GetSize for ArrayWithRank101111<S, H> {
     fn size_bytes_dyn(&self) -> usize {
         self.content.size_bytes_dyn() + self.l2ranks.size_bytes_dyn() + self.l1ranks.size_bytes_dyn()
+            + self.select1_hints.size_bytes_dyn() + self.select0_hints.size_bytes_dyn()
+    }
+    const USES_DYN_MEM: bool = S::USES_DYN_MEM || H::USES_DYN_MEM;
+    fn size_report(&self) -> SizeReport {
+        SizeReport::of(self).field("content", &self.content).field("l1ranks", &self.l1ranks).field("l2ranks", &self.l2ranks)
+            .field("select1_hints", &self.select1_hints).field("select0_hints", &self.select0_hints)
     }
-    const USES_DYN_MEM: bool = true;
 }
 
-impl BitArrayWithRank for ArrayWithRank101111 {
-    fn build(content: Box<[u64]>) -> (Self, u64) {
-        let mut l1ranks = Vec::with_capacity(ceiling_div(content.len(), 1<<(32-6)));
-        let mut l2ranks = Vec::with_capacity(ceiling_div(content.len(), 32));
-        let mut current_total_rank: u64 = 0;
-        for content in content.chunks(1<<(32-6)) {  // each l1 chunk has 1<<32 bits = (1<<32)/64 content elements
-            l1ranks.push(current_total_rank);
-            let mut current_rank: u64 = 0;
-            for chunk in content.chunks(32) {   // each chunk has 32*64 = 2048 bits
-                let mut to_append = current_rank;
-                let mut vals = chunk.chunks(8).map(|c| count_bits_in(c)); // each val has 8*64 = 512 bits
+impl ArrayWithRank101111 {
+    /// Returns the 10, 11 and 11-bit deltas of l2 entry (without the 32-bit rank) and the number of ones
+    /// for the given `chunk` of (up to) 2048 bits.
+    fn l2_deltas(chunk: &[u64]) -> (u64, u64) {
+        let mut deltas = 0;
+        let mut chunk_sum = 0;
+        let mut vals = chunk.chunks(8).map(count_bits_in); // each val has 8*64 = 512 bits
+        if let Some(v) = vals.next() {
+            chunk_sum = v;  // now chunk_sum uses up to 10 bits
+            deltas |= chunk_sum << (32+11+11);
+            if let Some(v) = vals.next() {
+                chunk_sum += v;     // now chunk_sum uses up to 11 bits
+                deltas |= chunk_sum << (32+11);
                 if let Some(v) = vals.next() {
-                    let mut chunk_sum = v;  // now chunk_sum uses up to 10 bits
-                    to_append |= chunk_sum << (32+11+11);
-                    if let Some(v) = vals.next() {
-                        chunk_sum += v;     // now chunk_sum uses up to 11 bits
-                        to_append |= chunk_sum << (32+11);
-                        if let Some(v) = vals.next() {
-                            chunk_sum += v;     // now chunk_sum uses up to 11 bits
-                            to_append |= chunk_sum << 32;
-                            if let Some(v) = vals.next() { chunk_sum += v; }
-                        }
-                    }
-                    current_rank += chunk_sum;
+                    chunk_sum += v;     // now chunk_sum uses up to 11 bits
+                    deltas |= chunk_sum << 32;
+                    if let Some(v) = vals.next() { chunk_sum += v; }
                 }
-                l2ranks.push(to_append);
             }
-            current_total_rank += current_rank;
         }
-        (Self{content, l1ranks: l1ranks.into_boxed_slice(), l2ranks: l2ranks.into_boxed_slice()}, current_total_rank)
+        (deltas, chunk_sum)
+    }
+
+    /// Constructs `Self` for the content of `content_len` words and `deltas` (calculated by [`Self::l2_deltas`])
+    /// of its subsequent 2048-bit chunks. Returns also the number of ones in the content.
+    ///
+    /// The `content` of the result is empty and should be set by the caller,
+    /// which allows `deltas` to be lazily calculated from the (borrowed) content.
+    fn with_l2_deltas(content_len: usize, deltas: impl IntoIterator<Item = (u64, u64)>) -> (Self, u64) {
+        let mut l1ranks = Vec::with_capacity(ceiling_div(content_len, 1<<(32-6)));
+        let mut l2ranks = Vec::with_capacity(ceiling_div(content_len, 32));
+        let mut select1_hints = Vec::new();
+        let mut select0_hints = Vec::new();
+        let (mut next_hinted_one, mut next_hinted_zero) = (0, 0);
+        let mut current_total_rank: u64 = 0;
+        let mut current_rank: u64 = 0;
+        for (block_index, (deltas, chunk_sum)) in deltas.into_iter().enumerate() {
+            if block_index % (1<<(32-11)) == 0 {    // each l1 chunk has 1<<32 bits = (1<<32)/2048 l2 chunks
+                current_total_rank += current_rank;
+                current_rank = 0;
+                l1ranks.push(current_total_rank);
+            }
+            l2ranks.push(current_rank | deltas);
+            current_rank += chunk_sum;
+            let ones = current_total_rank + current_rank;
+            const TOO_LONG: &str = "ArrayWithRank101111: too many l2 blocks to index them by 32-bit select hints";
+            push_hints(&mut select1_hints, &mut next_hinted_one, ones, block_index).expect(TOO_LONG);
+            let bits = ((block_index + 1) * 2048).min(content_len * 64);
+            push_hints(&mut select0_hints, &mut next_hinted_zero, bits as u64 - ones, block_index).expect(TOO_LONG);
+        }
+        current_total_rank += current_rank;
+        (Self{content: Box::default(), l1ranks: l1ranks.into_boxed_slice(), l2ranks: l2ranks.into_boxed_slice(),
+            select1_hints: select1_hints.into_boxed_slice(), select0_hints: select0_hints.into_boxed_slice()}, current_total_rank)
     }
 
-    fn rank(&self, index: usize) -> u64 {
+    /// Constructs `Self` for the `content`, using multiple threads.
+    /// Returns also the number of ones in the `content`.
+    ///
+    /// The result is identical to the one returned by [`BitArrayWithRank::build`].
+    #[cfg(feature = "rayon")]
+    pub fn build_par(content: Box<[u64]>) -> (Self, u64) {
+        use rayon::prelude::*;
+        let deltas: Vec<_> = content.par_chunks(32).map(Self::l2_deltas).collect();
+        let (mut result, ones) = Self::with_l2_deltas(content.len(), deltas);
+        result.content = content;
+        (result, ones)
+    }
+}
+
+impl BitArrayWithRank for ArrayWithRank101111 {
+    fn build(content: Box<[u64]>) -> (Self, u64) {
+        let (mut result, ones) = Self::with_l2_deltas(content.len(), content.chunks(32).map(Self::l2_deltas));
+        result.content = content;
+        (result, ones)
+    }
+}
+
+impl<S: Deref<Target = [u64]>, H: Deref<Target = [u32]>> Rank for ArrayWithRank101111<S, H> {
+    #[inline] fn rank(&self, index: usize) -> u64 {
+        Self::rank(self, index)
+    }
+
+    #[inline] fn get_bit(&self, index: usize) -> bool {
+        Self::get_bit(self, index)
+    }
+}
+
+impl<S: Deref<Target = [u64]>, H: Deref<Target = [u32]>> ArrayWithRank101111<S, H> {
+    /// Returns the bit of the `content` with given `index`.
+    #[inline] pub fn get_bit(&self, index: usize) -> bool {
+        self.content[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Returns iterator over the words of the `content`.
+    #[inline] pub fn content_words(&self) -> impl ExactSizeIterator<Item = u64> + '_ {
+        self.content.iter().copied()
+    }
+
+    /// Returns the number of ones in first `index` bits of the `content`.
+    pub fn rank(&self, index: usize) -> u64 {
         let block = index / 512;
         let mut block_content =  self.l2ranks[index/2048];//self.ranks[block/4];
-        let mut r = unsafe{ *self.l1ranks.get_unchecked(index >> 32) } + block_content & 0xFFFFFFFFu64; // 32 lowest bits   // for 34 bits: 0x3FFFFFFFFu64
+        // SAFETY: index/2048 < l2ranks.len(), which (checked by build, read and from_words) implies index>>32 < l1ranks.len()
+        let mut r = unsafe{ *self.l1ranks.get_unchecked(index >> 32) } + (block_content & 0xFFFFFFFFu64); // 32 lowest bits   // for 34 bits: 0x3FFFFFFFFu64
         block_content >>= 32;   // remove the lowest 32 bits
         r += (block_content >> (33 - 11 * (block & 3))) & 0b1_11111_11111;
         let word_idx = index / 64;
@@ -84,62 +251,368 @@ impl BitArrayWithRank for ArrayWithRank101111 {
         }*/
         r + (self.content[word_idx] & n_lowest_bits(index as u8 % 64)).count_ones() as u64
     }
+
+    /// Returns the position of the `rank`-th one (counting from 0) in the `content`
+    /// or `None` if there are no more than `rank` ones in the `content`.
+    #[inline] pub fn select1(&self, rank: u64) -> Option<usize> {
+        self.select::<true>(&self.select1_hints, rank)
+    }
+
+    /// Returns the position of the `rank`-th zero (counting from 0) in the `content`
+    /// or `None` if there are no more than `rank` zeros in the `content`.
+    #[inline] pub fn select0(&self, rank: u64) -> Option<usize> {
+        self.select::<false>(&self.select0_hints, rank)
+    }
+
+    /// Returns the index of the first one in the `content` which is not less than `from`, or `None` if there is no such one.
+    /// Uses the rank and select structures to skip the 512-bit blocks without ones.
+    pub fn next_one(&self, from: usize) -> Option<usize> {
+        next_with_rank::<true, _, _>(&self.content, from, |i| self.rank(i), |r| self.select1(r))
+    }
+
+    /// Returns the index of the last one in the `content` which is not greater than `from`, or `None` if there is no such one.
+    /// Uses the rank and select structures to skip the 512-bit blocks without ones.
+    pub fn prev_one(&self, from: usize) -> Option<usize> {
+        prev_with_rank::<true, _, _>(&self.content, from, |i| self.rank(i), |r| self.select1(r))
+    }
+
+    /// Returns the index of the first zero in the `content` which is not less than `from`, or `None` if there is no such zero.
+    /// Uses the rank and select structures to skip the 512-bit blocks without zeros.
+    pub fn next_zero(&self, from: usize) -> Option<usize> {
+        next_with_rank::<false, _, _>(&self.content, from, |i| self.rank(i), |r| self.select0(r))
+    }
+
+    /// Returns the index of the last zero in the `content` which is not greater than `from`, or `None` if there is no such zero.
+    /// Uses the rank and select structures to skip the 512-bit blocks without zeros.
+    pub fn prev_zero(&self, from: usize) -> Option<usize> {
+        prev_with_rank::<false, _, _>(&self.content, from, |i| self.rank(i), |r| self.select0(r))
+    }
+
+    /// Returns the number of ones in all l2 blocks (of 2048 bits) preceding the one with given index.
+    #[inline] fn ones_before_l2_block(&self, block: usize) -> u64 {
+        self.l1ranks[block >> (32-11)] + (self.l2ranks[block] & 0xFFFFFFFFu64)
+    }
+
+    /// Returns the position of the `rank`-th one (if `ONE` is `true`) or zero (otherwise)
+    /// in the `content`, using the select `hints` for ones (zeros).
+    fn select<const ONE: bool>(&self, hints: &[u32], rank: u64) -> Option<usize> {
+        let (begin, end) = hinted_blocks(hints, rank, self.l2ranks.len())?;
+        let before = |block: usize| {
+            let ones = self.ones_before_l2_block(block);
+            if ONE { ones } else { (block * 2048) as u64 - ones }
+        };
+        let block = last_satisfying(begin, end, |b| before(b) <= rank);
+        let mut rank = rank - before(block);
+        let deltas = self.l2ranks[block] >> 32;
+        let sub_blocks = ceiling_div(self.content.len() - block * 32, 8).min(4);
+        let mut sub_block = 0;
+        let mut sub_block_before = 0;
+        for (s, ones) in [(1, deltas >> 22), (2, (deltas >> 11) & 0b1_11111_11111), (3, deltas & 0b1_11111_11111)].into_iter().take(sub_blocks - 1) {
+            let b = if ONE { ones } else { s * 512 - ones };
+            if b > rank { break; }
+            sub_block = s as usize;
+            sub_block_before = b;
+        }
+        rank -= sub_block_before;
+        select_in_block::<ONE>(&self.content, block * 32 + sub_block * 8, rank)
+    }
+
+    /// Returns number of bytes which `write` will write.
+    pub fn write_bytes(&self) -> usize {
+        8 * (5 + self.content.len() + self.l1ranks.len() + self.l2ranks.len()
+            + u32::padded_words(self.select1_hints.len()) + u32::padded_words(self.select0_hints.len()))
+    }
+
+    /// Writes `self` to the `output`, including the rank and select structures.
+    ///
+    /// The lengths of all arrays (as `u64` values) and then the arrays (hints padded to the multiple of 64 bits)
+    /// are written in little-endian byte order,
+    /// so (on little-endian targets) the written words can be wrapped in place by [`ArrayWithRank101111::from_words`].
+    pub fn write(&self, output: &mut dyn io::Write) -> io::Result<()> {
+        for len in [self.content.len(), self.l1ranks.len(), self.l2ranks.len(), self.select1_hints.len(), self.select0_hints.len()] {
+            AsIs::write(output, len as u64)?;
+        }
+        for array in [&self.content, &self.l1ranks, &self.l2ranks] { AsIs::write_all(output, array.iter())?; }
+        u32::write_padded(output, &self.select1_hints)?;
+        u32::write_padded(output, &self.select0_hints)
+    }
+
+    /// Returns [`io::ErrorKind::InvalidData`] error if the lengths of the arrays or the select hints
+    /// are inconsistent, which could lead to undefined behaviour of the queries.
+    fn check(self) -> io::Result<Self> {
+        let blocks = self.l2ranks.len();
+        if self.l1ranks.len() != ceiling_div(self.content.len(), 1<<(32-6)) || blocks != ceiling_div(self.content.len(), 32) ||
+            !hints_are_valid(&self.select1_hints, blocks) || !hints_are_valid(&self.select0_hints, blocks) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "ArrayWithRank101111: inconsistent lengths of the rank structure"));
+        }
+        Ok(self)
+    }
 }
 
-/// The structure that holds array of bits `content` and `ranks` structure that takes no more than 6.25% extra space.
+impl ArrayWithRank101111 {
+    /// Reads `Self` written by [`ArrayWithRank101111::write`] from the `input`, without rebuilding the rank structure.
+    pub fn read(input: &mut dyn io::Read) -> io::Result<Self> {
+        let mut lens = [0u64; 5];
+        for len in lens.iter_mut() { *len = AsIs::read(input)?; }
+        Self { content: AsIs::read_n(input, lens[0] as usize)?, l1ranks: AsIs::read_n(input, lens[1] as usize)?,
+            l2ranks: AsIs::read_n(input, lens[2] as usize)?,
+            select1_hints: u32::read_padded(input, lens[3] as usize)?, select0_hints: u32::read_padded(input, lens[4] as usize)? }.check()
+    }
+}
+
+#[cfg(target_endian = "little")]
+impl<'a> ArrayWithRank101111<&'a [u64], &'a [u32]> {
+    /// Wraps, without copying, the prefix of `words` written by [`ArrayWithRank101111::write`].
+    /// Returns the wrapping structure and the rest of `words`.
+    pub fn from_words(words: &'a [u64]) -> io::Result<(Self, &'a [u64])> {
+        let (lens, words) = split_words(words, 5)?;
+        let (content, words) = split_words(words, lens[0])?;
+        let (l1ranks, words) = split_words(words, lens[1])?;
+        let (l2ranks, words) = split_words(words, lens[2])?;
+        let (select1_hints, words) = split_counters(words, lens[3])?;
+        let (select0_hints, words) = split_counters(words, lens[4])?;
+        Ok((Self { content, l1ranks, l2ranks, select1_hints, select0_hints }.check()?, words))
+    }
+}
+
+impl<S: Deref<Target = [u64]>, H: Deref<Target = [u32]>> BitArrayWithRankSelect for ArrayWithRank101111<S, H> {
+    #[inline] fn select1(&self, rank: u64) -> Option<usize> {
+        Self::select1(self, rank)
+    }
+
+    #[inline] fn select0(&self, rank: u64) -> Option<usize> {
+        Self::select0(self, rank)
+    }
+}
+
+/// The structure that holds array of bits `content` and `ranks` structure that takes no more than 6.25% extra space
+/// (or 12.5% if ranks are stored as `u64` values).
 /// It can returns the number of ones in first `index` bits of the `content` (see `rank` method) in *O(1)* time.
+/// Additionally, it holds sampled select hints which allow to quickly find positions of ones and zeros
+/// of given ranks (see `select1` and `select0` methods), by binary searching the 512-bit blocks between two subsequent hints
+/// (like in [`ArrayWithRank101111`], it is *O(1)* if the density of ones (zeros) is bounded from below, but *O(log n)* in the worst case).
+///
+/// The `content` is stored in `S`, which can be any type that dereferences to `[u64]`,
+/// and the ranks and hints are stored in `R`, which can be any type that dereferences to `[C]`,
+/// where `C` is a [`RankCounter`]: `u32` (default, which limits the number of ones in the `content` to *2^32-1*)
+/// or `u64` (see [`ArrayWithRankSimple64`]).
+/// By default, they are owned, but they can be also, for example, borrowed from a memory-mapped file
+/// (see [`ArrayWithRankSimple::write`] and [`ArrayWithRankSimple::from_words`]).
 #[derive(Clone)]
-pub struct ArrayWithRankSimple {
-    pub content: Box<[u64]>,  // BitVec
-    pub ranks: Box<[u32]>,
+pub struct ArrayWithRankSimple<S = Box<[u64]>, R = Box<[u32]>> {
+    pub content: S,  // BitVec
+    pub ranks: R,
+    pub select1_hints: R,  // Indices of 512-bit blocks that contain the ones with ranks 0, 8192, 2*8192, ...
+    pub select0_hints: R   // Indices of 512-bit blocks that contain the zeros with ranks 0, 8192, 2*8192, ...
 }
 
-impl GetSize for ArrayWithRankSimple {
+/// [`ArrayWithRankSimple`] that stores ranks as `u64` values and therefore supports any number of ones.
+pub type ArrayWithRankSimple64 = ArrayWithRankSimple<Box<[u64]>, Box<[u64]>>;
+
+impl<S: GetSize, R: GetSize> GetSize for ArrayWithRankSimple<S, R> {
     fn size_bytes_dyn(&self) -> usize {
         self.content.size_bytes_dyn() + self.ranks.size_bytes_dyn()
+            + self.select1_hints.size_bytes_dyn() + self.select0_hints.size_bytes_dyn()
+    }
+    const USES_DYN_MEM: bool = S::USES_DYN_MEM || R::USES_DYN_MEM;
+    fn size_report(&self) -> SizeReport {
+        SizeReport::of(self).field("content", &self.content).field("ranks", &self.ranks)
+            .field("select1_hints", &self.select1_hints).field("select0_hints", &self.select0_hints)
     }
-    const USES_DYN_MEM: bool = true;
 }
 
-impl ArrayWithRankSimple {
-
+impl<C: RankCounter> ArrayWithRankSimple<Box<[u64]>, Box<[C]>> {
     /// Constructs `ArrayWithRankSimple` and count number of bits set in `content`. Returns both.
-    pub fn build(content: Box<[u64]>) -> (Self, u32) {
+    ///
+    /// Panics if the number of ones (or blocks) in `content` cannot be represented by `C` (see [`Self::try_build`]).
+    pub fn build(content: Box<[u64]>) -> (Self, C) {
+        match Self::try_build(content) {
+            Ok((result, ones)) => (result, C::try_from(ones).ok().unwrap()),
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Constructs `ArrayWithRankSimple` and count number of bits set in `content`. Returns both,
+    /// or [`io::ErrorKind::InvalidInput`] error if the number of ones (or 512-bit blocks) in `content`
+    /// cannot be represented by `C`.
+    pub fn try_build(content: Box<[u64]>) -> io::Result<(Self, u64)> {
+        let overflow = |what| io::Error::new(io::ErrorKind::InvalidInput,
+            format!("ArrayWithRankSimple: too many {} in content for the type of rank counters", what));
         let mut result = Vec::with_capacity(ceiling_div(content.len(), 8usize));
-        let mut current_rank: u32 = 0;
-        for seg_nr in 0..content.len() {
-            if seg_nr % 8 == 0 { result.push(current_rank); }
-            current_rank += content[seg_nr].count_ones();
+        let mut select1_hints = Vec::new();
+        let mut select0_hints = Vec::new();
+        let (mut next_hinted_one, mut next_hinted_zero) = (0, 0);
+        let mut current_rank: u64 = 0;
+        for (block_index, block) in content.chunks(8).enumerate() {
+            result.push(C::try_from(current_rank).map_err(|_| overflow("ones"))?);
+            current_rank += count_bits_in(block);
+            push_hints(&mut select1_hints, &mut next_hinted_one, current_rank, block_index).map_err(|_| overflow("blocks"))?;
+            push_hints(&mut select0_hints, &mut next_hinted_zero, (block_index * 512 + block.len() * 64) as u64 - current_rank, block_index)
+                .map_err(|_| overflow("blocks"))?;
         }
-        (Self{content, ranks: result.into_boxed_slice()}, current_rank)
+        if C::try_from(current_rank).is_err() { return Err(overflow("ones")); }
+        Ok((Self{content, ranks: result.into_boxed_slice(),
+            select1_hints: select1_hints.into_boxed_slice(), select0_hints: select0_hints.into_boxed_slice()}, current_rank))
     }
 
-    pub fn rank(&self, index: usize) -> u32 {
+    /// Reads `Self` written by [`ArrayWithRankSimple::write`] from the `input`, without rebuilding the rank structure.
+    pub fn read(input: &mut dyn io::Read) -> io::Result<Self> {
+        let mut lens = [0u64; 4];
+        for len in lens.iter_mut() { *len = AsIs::read(input)?; }
+        Self { content: AsIs::read_n(input, lens[0] as usize)?, ranks: C::read_padded(input, lens[1] as usize)?,
+            select1_hints: C::read_padded(input, lens[2] as usize)?, select0_hints: C::read_padded(input, lens[3] as usize)? }.check()
+    }
+}
+
+#[cfg(target_endian = "little")]
+impl<'a, C: RankCounter> ArrayWithRankSimple<&'a [u64], &'a [C]> {
+    /// Wraps, without copying, the prefix of `words` written by [`ArrayWithRankSimple::write`].
+    /// Returns the wrapping structure and the rest of `words`.
+    pub fn from_words(words: &'a [u64]) -> io::Result<(Self, &'a [u64])> {
+        let (lens, words) = split_words(words, 4)?;
+        let (content, words) = split_words(words, lens[0])?;
+        let (ranks, words) = split_counters(words, lens[1])?;
+        let (select1_hints, words) = split_counters(words, lens[2])?;
+        let (select0_hints, words) = split_counters(words, lens[3])?;
+        Ok((Self { content, ranks, select1_hints, select0_hints }.check()?, words))
+    }
+}
+
+impl<S: Deref<Target = [u64]>, C: RankCounter, R: Deref<Target = [C]>> ArrayWithRankSimple<S, R> {
+    /// Returns the bit of the `content` with given `index`.
+    #[inline] pub fn get_bit(&self, index: usize) -> bool {
+        self.content[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Returns iterator over the words of the `content`.
+    #[inline] pub fn content_words(&self) -> impl ExactSizeIterator<Item = u64> + '_ {
+        self.content.iter().copied()
+    }
+
+    /// Returns the number of ones in first `index` bits of the `content`.
+    pub fn rank(&self, index: usize) -> C {
         let word_idx = index / 64;
         let word_offset = index as u8 % 64;
         let block = index / 512;
-        let mut r = self.ranks[block];
+        let mut r = 0;
         for w in block * (512 / 64)..word_idx {
             r += self.content[w].count_ones();
         }
-        r + (self.content[word_idx] & n_lowest_bits(word_offset)).count_ones() as u32
+        r += (self.content[word_idx] & n_lowest_bits(word_offset)).count_ones();
+        self.ranks[block] + C::from(r)
+    }
+
+    /// Returns the position of the `rank`-th one (counting from 0) in the `content`
+    /// or `None` if there are no more than `rank` ones in the `content`.
+    #[inline] pub fn select1(&self, rank: u64) -> Option<usize> {
+        self.select::<true>(&self.select1_hints, rank)
+    }
+
+    /// Returns the position of the `rank`-th zero (counting from 0) in the `content`
+    /// or `None` if there are no more than `rank` zeros in the `content`.
+    #[inline] pub fn select0(&self, rank: u64) -> Option<usize> {
+        self.select::<false>(&self.select0_hints, rank)
+    }
+
+    /// Returns the index of the first one in the `content` which is not less than `from`, or `None` if there is no such one.
+    /// Uses the rank and select structures to skip the 512-bit blocks without ones.
+    pub fn next_one(&self, from: usize) -> Option<usize> {
+        next_with_rank::<true, _, _>(&self.content, from, |i| self.rank(i).into(), |r| self.select1(r))
+    }
+
+    /// Returns the index of the last one in the `content` which is not greater than `from`, or `None` if there is no such one.
+    /// Uses the rank and select structures to skip the 512-bit blocks without ones.
+    pub fn prev_one(&self, from: usize) -> Option<usize> {
+        prev_with_rank::<true, _, _>(&self.content, from, |i| self.rank(i).into(), |r| self.select1(r))
+    }
+
+    /// Returns the index of the first zero in the `content` which is not less than `from`, or `None` if there is no such zero.
+    /// Uses the rank and select structures to skip the 512-bit blocks without zeros.
+    pub fn next_zero(&self, from: usize) -> Option<usize> {
+        next_with_rank::<false, _, _>(&self.content, from, |i| self.rank(i).into(), |r| self.select0(r))
+    }
+
+    /// Returns the index of the last zero in the `content` which is not greater than `from`, or `None` if there is no such zero.
+    /// Uses the rank and select structures to skip the 512-bit blocks without zeros.
+    pub fn prev_zero(&self, from: usize) -> Option<usize> {
+        prev_with_rank::<false, _, _>(&self.content, from, |i| self.rank(i).into(), |r| self.select0(r))
+    }
+
+    /// Returns number of bytes which `write` will write.
+    pub fn write_bytes(&self) -> usize {
+        8 * (4 + self.content.len() + C::padded_words(self.ranks.len())
+            + C::padded_words(self.select1_hints.len()) + C::padded_words(self.select0_hints.len()))
+    }
+
+    /// Writes `self` to the `output`, including the rank and select structures.
+    ///
+    /// The lengths of all arrays (as `u64` values) and then the arrays (each padded to the multiple of 64 bits)
+    /// are written in little-endian byte order,
+    /// so (on little-endian targets) the written words can be wrapped in place by [`ArrayWithRankSimple::from_words`].
+    pub fn write(&self, output: &mut dyn io::Write) -> io::Result<()> {
+        for len in [self.content.len(), self.ranks.len(), self.select1_hints.len(), self.select0_hints.len()] {
+            AsIs::write(output, len as u64)?;
+        }
+        AsIs::write_all(output, self.content.iter())?;
+        C::write_padded(output, &self.ranks)?;
+        C::write_padded(output, &self.select1_hints)?;
+        C::write_padded(output, &self.select0_hints)
+    }
+
+    /// Returns [`io::ErrorKind::InvalidData`] error if the lengths of the arrays or the select hints are inconsistent.
+    fn check(self) -> io::Result<Self> {
+        let blocks = self.ranks.len();
+        if blocks != ceiling_div(self.content.len(), 8) ||
+            !hints_are_valid(&self.select1_hints, blocks) || !hints_are_valid(&self.select0_hints, blocks) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "ArrayWithRankSimple: inconsistent lengths of the rank structure"));
+        }
+        Ok(self)
+    }
+
+    /// Returns the position of the `rank`-th one (if `ONE` is `true`) or zero (otherwise)
+    /// in the `content`, using the select `hints` for ones (zeros).
+    fn select<const ONE: bool>(&self, hints: &[C], rank: u64) -> Option<usize> {
+        let (begin, end) = hinted_blocks(hints, rank, self.ranks.len())?;
+        let before = |block: usize| {
+            let ones: u64 = self.ranks[block].into();
+            if ONE { ones } else { (block * 512) as u64 - ones }
+        };
+        let block = last_satisfying(begin, end, |b| before(b) <= rank);
+        select_in_block::<ONE>(&self.content, block * 8, rank - before(block))
     }
 }
 
-impl BitArrayWithRank for ArrayWithRankSimple {
+impl<C: RankCounter> BitArrayWithRank for ArrayWithRankSimple<Box<[u64]>, Box<[C]>> {
     #[inline(always)] fn build(content: Box<[u64]>) -> (Self, u64) {
         let (r, s) = Self::build(content);
-        (r, s as u64)
+        (r, s.into())
     }
+}
 
+impl<S: Deref<Target = [u64]>, C: RankCounter, R: Deref<Target = [C]>> Rank for ArrayWithRankSimple<S, R> {
     #[inline(always)] fn rank(&self, index: usize) -> u64 {
-        Self::rank(self, index) as u64
+        Self::rank(self, index).into()
+    }
+
+    #[inline] fn get_bit(&self, index: usize) -> bool {
+        Self::get_bit(self, index)
+    }
+}
+
+impl<S: Deref<Target = [u64]>, C: RankCounter, R: Deref<Target = [C]>> BitArrayWithRankSelect for ArrayWithRankSimple<S, R> {
+    #[inline] fn select1(&self, rank: u64) -> Option<usize> {
+        Self::select1(self, rank)
+    }
+
+    #[inline] fn select0(&self, rank: u64) -> Option<usize> {
+        Self::select0(self, rank)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::BitAccess;
 
     fn test_array_with_rank<ArrayWithRank: BitArrayWithRank>() {
         let (a, c) = ArrayWithRank::build(vec![0b1101, 0b110].into_boxed_slice());
@@ -222,4 +695,209 @@ mod tests {
     fn content_simple() {
         test_content::<ArrayWithRankSimple>();
     }
+
+    fn test_select<ArrayWithRank: BitArrayWithRank + BitArrayWithRankSelect>(content: Box<[u64]>) {
+        let bits = content.len() * 64;
+        let ones: Vec<usize> = (0..bits).filter(|i| content[i / 64] & (1 << (i % 64)) != 0).collect();
+        let zeros: Vec<usize> = (0..bits).filter(|i| content[i / 64] & (1 << (i % 64)) == 0).collect();
+        let (a, c) = ArrayWithRank::build(content);
+        assert_eq!(c as usize, ones.len());
+        for (rank, position) in ones.iter().enumerate() {
+            assert_eq!(a.select1(rank as u64), Some(*position), "select1({})", rank);
+        }
+        assert_eq!(a.select1(ones.len() as u64), None);
+        assert_eq!(a.select1(ones.len() as u64 + 1000), None);
+        for (rank, position) in zeros.iter().enumerate() {
+            assert_eq!(a.select0(rank as u64), Some(*position), "select0({})", rank);
+        }
+        assert_eq!(a.select0(zeros.len() as u64), None);
+        assert_eq!(a.select0(zeros.len() as u64 + 1000), None);
+    }
+
+    fn test_selects<ArrayWithRank: BitArrayWithRank + BitArrayWithRankSelect>() {
+        test_select::<ArrayWithRank>(vec![].into_boxed_slice());
+        test_select::<ArrayWithRank>(vec![0b1101, 0b110].into_boxed_slice());
+        test_select::<ArrayWithRank>(vec![0b1101; 60].into_boxed_slice());
+        test_select::<ArrayWithRank>(vec![u64::MAX; 35].into_boxed_slice());
+        test_select::<ArrayWithRank>(vec![0; 35].into_boxed_slice());
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;  // xorshift generator of pseudo-random content
+        test_select::<ArrayWithRank>((0..2000).map(|i| {
+            x ^= x << 13; x ^= x >> 7; x ^= x << 17;
+            if i % 300 < 100 { x & (x >> 3) } else { x }
+        }).collect());
+    }
+
+    #[test]
+    fn select_101111() {
+        test_selects::<ArrayWithRank101111>();
+    }
+
+    #[test]
+    fn select_simple() {
+        test_selects::<ArrayWithRankSimple>();
+    }
+
+    #[test]
+    fn simple64() {
+        test_array_with_rank::<ArrayWithRankSimple64>();
+        test_big_array_with_rank::<ArrayWithRankSimple64>();
+        test_content::<ArrayWithRankSimple64>();
+        test_selects::<ArrayWithRankSimple64>();
+        let (a, ones) = ArrayWithRankSimple64::try_build(pseudo_random_content(101)).unwrap();
+        assert_eq!(ones, a.content.count_bit_ones() as u64);
+        let mut buff = Vec::new();
+        a.write(&mut buff).unwrap();
+        assert_eq!(buff.len(), a.write_bytes());
+        let read = ArrayWithRankSimple64::read(&mut &buff[..]).unwrap();
+        assert_eq!(read.ranks, a.ranks);
+        #[cfg(target_endian = "little")] {
+            let words: Vec<u64> = buff.chunks(8).map(|c| u64::from_le_bytes(c.try_into().unwrap())).collect();
+            let (wrapped, _) = ArrayWithRankSimple::<_, &[u64]>::from_words(&words).unwrap();
+            for i in (0..101*64).step_by(5) { assert_eq!(wrapped.rank(i), a.rank(i)); }
+        }
+    }
+
+    #[test]
+    fn rank_101111_above_u32() {
+        let (mut a, _) = ArrayWithRank101111::build(pseudo_random_content(100));
+        let expected: Vec<u64> = (0..100*64).map(|i| a.rank(i) + (1 << 33)).collect();
+        a.l1ranks[0] += 1 << 33;   // as if there were 2^33 ones before the content
+        for (i, r) in expected.into_iter().enumerate() { assert_eq!(a.rank(i), r); }
+    }
+
+    /// Regression test: `rank` used to compute `(l1 + l2) & 0xFFFFFFFF` instead of `l1 + (l2 & 0xFFFFFFFF)`,
+    /// which dropped the carry whenever the sum of the l1 and l2 ranks exceeded 32 bits.
+    #[test]
+    fn rank_101111_l1_l2_carry() {
+        let (mut a, _) = ArrayWithRank101111::build(vec![u64::MAX; 64].into_boxed_slice());
+        a.l1ranks[0] = u32::MAX as u64;   // as if there were 2^32-1 ones before the content
+        assert_eq!(a.rank(0), u32::MAX as u64);
+        assert_eq!(a.rank(2048), u32::MAX as u64 + 2048);
+        assert_eq!(a.rank(2048 + 513), u32::MAX as u64 + 2048 + 513);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn build_par_101111() {
+        for len in [0, 1, 31, 32, 33, 1000, 100_001] {
+            let content = pseudo_random_content(len);
+            let (a, ones) = ArrayWithRank101111::build(content.clone());
+            let (p, p_ones) = ArrayWithRank101111::build_par(content);
+            assert_eq!(ones, p_ones);
+            assert_eq!(a.l1ranks, p.l1ranks);
+            assert_eq!(a.l2ranks, p.l2ranks);
+            assert_eq!(a.select1_hints, p.select1_hints);
+            assert_eq!(a.select0_hints, p.select0_hints);
+        }
+    }
+
+    fn test_next_prev(content: Box<[u64]>) {
+        let len = content.len() * 64;
+        let a = ArrayWithRank101111::build(content.clone()).0;
+        let s = <ArrayWithRankSimple>::build(content.clone()).0;
+        for i in (0..len + 100).step_by(3) {
+            assert_eq!(a.next_one(i), content.next_one(i), "next_one({})", i);
+            assert_eq!(a.prev_one(i), content.prev_one(i), "prev_one({})", i);
+            assert_eq!(a.next_zero(i), content.next_zero(i), "next_zero({})", i);
+            assert_eq!(a.prev_zero(i), content.prev_zero(i), "prev_zero({})", i);
+            assert_eq!(s.next_one(i), content.next_one(i), "next_one({})", i);
+            assert_eq!(s.prev_one(i), content.prev_one(i), "prev_one({})", i);
+            assert_eq!(s.next_zero(i), content.next_zero(i), "next_zero({})", i);
+            assert_eq!(s.prev_zero(i), content.prev_zero(i), "prev_zero({})", i);
+        }
+    }
+
+    #[test]
+    fn next_prev() {
+        test_next_prev(Box::new([]));
+        let mut sparse = vec![0u64; 3000];
+        sparse[0] = 1 << 7;
+        sparse[40] = 1 << 63;
+        sparse[2999] = 1;
+        test_next_prev(sparse.into_boxed_slice());
+        let mut dense = vec![u64::MAX; 2000];
+        dense[100] = !(1 << 9);
+        dense[1001] = 0;
+        test_next_prev(dense.into_boxed_slice());
+        test_next_prev(pseudo_random_content(100));
+    }
+
+    fn pseudo_random_content(words: usize) -> Box<[u64]> {
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        (0..words).map(|_| { x ^= x << 13; x ^= x >> 7; x ^= x << 17; x }).collect()
+    }
+
+    #[test]
+    fn size_report_101111() {
+        let (a, _) = ArrayWithRank101111::build(pseudo_random_content(1000));
+        let report = a.size_report();
+        assert_eq!(report.name, "ArrayWithRank101111");
+        assert_eq!(report.bytes, a.size_bytes());
+        assert_eq!(report.children.len(), 5);
+        assert_eq!(report.children[0].name, "content");
+        assert_eq!(report.children[0].bytes, 1000 * 8 + std::mem::size_of::<Box<[u64]>>());
+        assert_eq!(report.children.iter().map(|c| c.bytes).sum::<usize>(), report.bytes);
+    }
+
+    /// Checks whether `a` and `b` answer the same to rank and select queries, using the traits.
+    fn assert_same_queries<A: BitArrayWithRankSelect, B: BitArrayWithRankSelect>(a: &A, b: &B, bits: usize) {
+        for i in (0..bits).step_by(7) {
+            assert_eq!(a.rank(i), b.rank(i));
+            assert_eq!(a.get_bit(i), b.get_bit(i));
+            assert_eq!(a.select1(i as u64), b.select1(i as u64));
+            assert_eq!(a.select0(i as u64), b.select0(i as u64));
+        }
+    }
+
+    #[test]
+    fn read_write_wrap_101111() {
+        let (a, _) = ArrayWithRank101111::build(pseudo_random_content(1000));
+        let mut buff = Vec::new();
+        a.write(&mut buff).unwrap();
+        assert_eq!(buff.len(), a.write_bytes());
+        let read = ArrayWithRank101111::read(&mut &buff[..]).unwrap();
+        assert_eq!(read.l2ranks, a.l2ranks);
+        assert_eq!(read.select0_hints, a.select0_hints);
+        #[cfg(target_endian = "little")] {
+            let mut words: Vec<u64> = buff.chunks(8).map(|c| u64::from_le_bytes(c.try_into().unwrap())).collect();
+            words.push(12345);
+            let (wrapped, rest) = ArrayWithRank101111::from_words(&words).unwrap();
+            assert_eq!(rest, [12345]);
+            assert_eq!(wrapped.size_bytes_dyn(), 0);
+            assert_same_queries(&wrapped, &a, 64000);
+            assert!(ArrayWithRank101111::from_words(&words[..words.len()-2]).is_err());
+        }
+    }
+
+    #[test]
+    fn read_wrap_inconsistent_101111() {
+        let (a, _) = ArrayWithRank101111::build(pseudo_random_content(100));
+        let mut buff = Vec::new();
+        a.write(&mut buff).unwrap();
+        buff[8..16].fill(0);    // l1ranks is empty
+        assert_eq!(ArrayWithRank101111::read(&mut &buff[..]).err().map(|e| e.kind()), Some(io::ErrorKind::InvalidData));
+        #[cfg(target_endian = "little")] {
+            let words: Vec<u64> = buff.chunks(8).map(|c| u64::from_le_bytes(c.try_into().unwrap())).collect();
+            assert_eq!(ArrayWithRank101111::from_words(&words).err().map(|e| e.kind()), Some(io::ErrorKind::InvalidData));
+        }
+    }
+
+    #[test]
+    fn read_write_wrap_simple() {
+        let (a, _) = <ArrayWithRankSimple>::build(pseudo_random_content(1001));
+        let mut buff = Vec::new();
+        a.write(&mut buff).unwrap();
+        assert_eq!(buff.len(), a.write_bytes());
+        let read = ArrayWithRankSimple::read(&mut &buff[..]).unwrap();
+        assert_eq!(read.ranks, a.ranks);
+        assert_eq!(read.select1_hints, a.select1_hints);
+        #[cfg(target_endian = "little")] {
+            let words: Vec<u64> = buff.chunks(8).map(|c| u64::from_le_bytes(c.try_into().unwrap())).collect();
+            let (wrapped, rest) = ArrayWithRankSimple::<_, &[u32]>::from_words(&words).unwrap();
+            assert!(rest.is_empty());
+            assert_same_queries(&wrapped, &a, 64064);
+        }
+        buff[8..16].fill(0);    // ranks is empty
+        assert_eq!(<ArrayWithRankSimple>::read(&mut &buff[..]).err().map(|e| e.kind()), Some(io::ErrorKind::InvalidData));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,348 @@
+use dyn_size_of::{GetSize, SizeReport};
+use super::{BitAccess, BitVec, BitArrayWithRank, ArrayWithRank101111, BitVector, ceiling_div};
+
+/// Number of bits in each block for which the minimum excess is stored.
+const BLOCK_BITS: usize = 512;
+
+/// Number of blocks in each superblock for which the (absolute) minimum excess is stored. Must be a power of 2.
+const SUPERBLOCK_BLOCKS: usize = 64;
+
+/// For each byte (whose bits are subsequent parentheses, from the least significant one):
+/// the excess of the byte, the minimum excess after each of its bits, and the minimum excess before each of its bits,
+/// all relative to the excess before the byte.
+static BYTE_EXCESS: [(i8, i8, i8); 256] = byte_excess_table();
+
+/// Returns the content of [`BYTE_EXCESS`].
+const fn byte_excess_table() -> [(i8, i8, i8); 256] {
+    let mut table = [(0, 0, 0); 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let (mut excess, mut forward_min, mut backward_min) = (0i8, i8::MAX, 0i8);
+        let mut bit = 0;
+        while bit < 8 {
+            if excess < backward_min { backward_min = excess; }
+            excess += if byte & (1 << bit) != 0 { 1 } else { -1 };
+            if excess < forward_min { forward_min = excess; }
+            bit += 1;
+        }
+        table[byte] = (excess, forward_min, backward_min);
+        byte += 1;
+    }
+    table
+}
+
+/// Sequence of balanced parentheses, in which ones are opening and zeros are closing parentheses.
+/// It supports `find_close`, `find_open` and `enclose` navigational queries.
+///
+/// The queries are answered by searching for positions with given excess (the number of opening
+/// minus the number of closing parentheses in the prefix), which is calculated in *O(1)* time
+/// using [`ArrayWithRank101111`]. The search skips the 512-bit blocks and 64-block superblocks
+/// that cannot contain the answer, as their minimum excesses are stored (using 16 bits per block,
+/// and in a min-tree over superblocks, which allows to find the nearest superblock that contains the answer in *O(log n)* time).
+/// Within a block, the parentheses are scanned byte by byte, using a lookup table.
+/// Thus, each query takes *O(log n)* time, and the structure takes up to 4% more space than the rank structure.
+#[derive(Clone)]
+pub struct BalancedParens {
+    bits: ArrayWithRank101111,  // parentheses with an extra word, which makes rank(len) valid
+    block_mins: Box<[i16]>,     // minimum excess in each block, relative to the excess at its beginning
+    superblock_tree: Box<[i64]>,    // min-tree (in heap order, leaves in the second half) over the minimum excesses of superblocks
+    len: usize  // number of parentheses
+}
+
+impl GetSize for BalancedParens {
+    fn size_bytes_dyn(&self) -> usize {
+        self.bits.size_bytes_dyn() + self.block_mins.size_bytes_dyn() + self.superblock_tree.size_bytes_dyn()
+    }
+    const USES_DYN_MEM: bool = true;
+    fn size_report(&self) -> SizeReport {
+        SizeReport::of(self).field("bits", &self.bits).field("block_mins", &self.block_mins).field("superblock_tree", &self.superblock_tree)
+    }
+}
+
+impl BalancedParens {
+    /// Constructs the sequence of parentheses given as `bits` (ones are opening and zeros are closing parentheses).
+    /// The sequence should be balanced, otherwise results of the queries are unspecified.
+    pub fn new(bits: BitVector) -> Self {
+        let len = bits.len();
+        let mut content = Box::<[u64]>::with_zeroed_bits(len + 1);
+        content[..bits.words().len()].copy_from_slice(bits.words());
+        let blocks = ceiling_div(len, BLOCK_BITS);
+        let mut block_mins = Vec::with_capacity(blocks);
+        let leaves = ceiling_div(blocks, SUPERBLOCK_BLOCKS).next_power_of_two();
+        let mut superblock_tree = vec![i64::MAX; 2 * leaves];
+        let mut excess = 0i64;
+        for block in 0..blocks {
+            let (mut relative, mut min) = (0i64, 0i64);
+            for index in block * BLOCK_BITS..((block + 1) * BLOCK_BITS).min(len) {
+                relative += if content.get_bit(index) { 1 } else { -1 };
+                min = min.min(relative);
+            }
+            block_mins.push(min as i16);
+            let superblock_min = &mut superblock_tree[leaves + block / SUPERBLOCK_BLOCKS];
+            *superblock_min = (*superblock_min).min(excess + min);
+            excess += relative;
+        }
+        for node in (1..leaves).rev() {
+            superblock_tree[node] = superblock_tree[2 * node].min(superblock_tree[2 * node + 1]);
+        }
+        Self {
+            bits: ArrayWithRank101111::build(content).0,
+            block_mins: block_mins.into_boxed_slice(),
+            superblock_tree: superblock_tree.into_boxed_slice(),
+            len
+        }
+    }
+
+    /// Returns the number of parentheses in the sequence.
+    #[inline] pub fn len(&self) -> usize { self.len }
+
+    /// Returns whether the sequence is empty.
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns whether the parenthesis at given `index` is opening.
+    #[inline] pub fn is_open(&self, index: usize) -> bool { self.bits.get_bit(index) }
+
+    /// Returns the number of opening minus the number of closing parentheses among the first `index` (at most `self.len()`).
+    #[inline] pub fn excess(&self, index: usize) -> i64 {
+        2 * self.bits.rank(index) as i64 - index as i64
+    }
+
+    /// Returns the entry of [`BYTE_EXCESS`] for the byte of parentheses that begins at `index` (which must be a multiple of 8).
+    #[inline] fn byte_excess(&self, index: usize) -> (i64, i64, i64) {
+        let (excess, forward_min, backward_min) = BYTE_EXCESS[(self.bits.content[index / 64] >> (index % 64)) as u8 as usize];
+        (excess as i64, forward_min as i64, backward_min as i64)
+    }
+
+    /// Returns the smallest position in range (`from`, `end`] with excess at most `target`,
+    /// given the excess at `from`.
+    fn scan_forward(&self, mut from: usize, end: usize, mut excess: i64, target: i64) -> Option<usize> {
+        while from < end {
+            if from & 7 == 0 && from + 8 <= end {
+                let (byte_excess, byte_min, _) = self.byte_excess(from);
+                if excess + byte_min > target {    // skip the whole byte
+                    excess += byte_excess;
+                    from += 8;
+                    continue;
+                }
+            }
+            excess += if self.bits.get_bit(from) { 1 } else { -1 };
+            from += 1;
+            if excess <= target { return Some(from); }
+        }
+        None
+    }
+
+    /// Returns the largest position in range [`begin`, `from`) with excess at most `target`,
+    /// given the excess at `from`.
+    fn scan_backward(&self, begin: usize, mut from: usize, mut excess: i64, target: i64) -> Option<usize> {
+        while from > begin {
+            if from & 7 == 0 && from >= begin + 8 {
+                let (byte_excess, _, byte_min) = self.byte_excess(from - 8);
+                if excess - byte_excess + byte_min > target {   // skip the whole byte
+                    excess -= byte_excess;
+                    from -= 8;
+                    continue;
+                }
+            }
+            from -= 1;
+            excess -= if self.bits.get_bit(from) { 1 } else { -1 };
+            if excess <= target { return Some(from); }
+        }
+        None
+    }
+
+    /// Returns the number of leaves of the min-tree over superblocks.
+    #[inline] fn tree_leaves(&self) -> usize { self.superblock_tree.len() / 2 }
+
+    /// Returns the smallest index of a superblock after `superblock` with minimum excess at most `target`.
+    fn next_superblock(&self, superblock: usize, target: i64) -> Option<usize> {
+        let leaves = self.tree_leaves();
+        let mut node = leaves + superblock;
+        loop {  // go up to the first node whose right sibling's subtree includes the superblock sought
+            if node == 1 { return None; }
+            if node & 1 == 0 && self.superblock_tree[node + 1] <= target { break node += 1; }
+            node /= 2;
+        }
+        while node < leaves {
+            node = if self.superblock_tree[2 * node] <= target { 2 * node } else { 2 * node + 1 };
+        }
+        Some(node - leaves)
+    }
+
+    /// Returns the largest index of a superblock before `superblock` with minimum excess at most `target`.
+    fn prev_superblock(&self, superblock: usize, target: i64) -> Option<usize> {
+        let leaves = self.tree_leaves();
+        let mut node = leaves + superblock;
+        loop {  // go up to the first node whose left sibling's subtree includes the superblock sought
+            if node == 1 { return None; }
+            if node & 1 == 1 && self.superblock_tree[node - 1] <= target { break node -= 1; }
+            node /= 2;
+        }
+        while node < leaves {
+            node = if self.superblock_tree[2 * node + 1] <= target { 2 * node + 1 } else { 2 * node };
+        }
+        Some(node - leaves)
+    }
+
+    /// Returns the smallest position with excess at most `target` in blocks [`first`, `end`).
+    fn forward_in_blocks(&self, first: usize, end: usize, target: i64) -> Option<usize> {
+        for block in first..end {
+            let begin = block * BLOCK_BITS;
+            let excess = self.excess(begin);
+            if excess + self.block_mins[block] as i64 <= target {
+                let result = self.scan_forward(begin, (begin + BLOCK_BITS).min(self.len), excess, target);
+                if result.is_some() { return result; }
+            }
+        }
+        None
+    }
+
+    /// Returns the largest position with excess at most `target` in blocks [`first`, `end`), which must be full.
+    fn backward_in_blocks(&self, first: usize, end: usize, target: i64) -> Option<usize> {
+        for block in (first..end).rev() {
+            let begin = block * BLOCK_BITS;
+            if self.excess(begin) + self.block_mins[block] as i64 <= target {
+                let end = begin + BLOCK_BITS;
+                let result = self.scan_backward(begin, end, self.excess(end), target);
+                if result.is_some() { return result; }
+            }
+        }
+        None
+    }
+
+    /// Returns the smallest position greater than `from` with excess at most `target`.
+    fn forward_search(&self, from: usize, target: i64) -> Option<usize> {
+        let block = from / BLOCK_BITS;
+        let result = self.scan_forward(from, ((block + 1) * BLOCK_BITS).min(self.len), self.excess(from), target);
+        if result.is_some() { return result; }
+        let mut superblock = block / SUPERBLOCK_BLOCKS;
+        let mut first = block + 1;
+        loop {
+            let end = ((superblock + 1) * SUPERBLOCK_BLOCKS).min(self.block_mins.len());
+            let result = self.forward_in_blocks(first, end, target);
+            if result.is_some() { return result; }
+            superblock = self.next_superblock(superblock, target)?;
+            first = superblock * SUPERBLOCK_BLOCKS;
+        }
+    }
+
+    /// Returns the largest position less than `from` with excess at most `target`.
+    fn backward_search(&self, from: usize, target: i64) -> Option<usize> {
+        if from == 0 { return None; }
+        let block = (from - 1) / BLOCK_BITS;
+        let result = self.scan_backward(block * BLOCK_BITS, from, self.excess(from), target);
+        if result.is_some() { return result; }
+        let mut superblock = block / SUPERBLOCK_BLOCKS;
+        let mut end = block;
+        loop {
+            let result = self.backward_in_blocks(superblock * SUPERBLOCK_BLOCKS, end, target);
+            if result.is_some() { return result; }
+            superblock = self.prev_superblock(superblock, target)?;
+            end = (superblock + 1) * SUPERBLOCK_BLOCKS;
+        }
+    }
+
+    /// Returns the index of the closing parenthesis that matches the opening one at given `index`,
+    /// or `None` if there is no opening parenthesis at `index`.
+    pub fn find_close(&self, index: usize) -> Option<usize> {
+        if index >= self.len || !self.is_open(index) { return None; }
+        self.forward_search(index + 1, self.excess(index)).map(|p| p - 1)
+    }
+
+    /// Returns the index of the opening parenthesis that matches the closing one at given `index`,
+    /// or `None` if there is no closing parenthesis at `index`.
+    pub fn find_open(&self, index: usize) -> Option<usize> {
+        if index >= self.len || self.is_open(index) { return None; }
+        self.backward_search(index, self.excess(index + 1))
+    }
+
+    /// Returns the index of the opening parenthesis of the nearest pair that encloses
+    /// the opening parenthesis at given `index`, or `None` if there is no such pair
+    /// (or there is no opening parenthesis at `index`).
+    pub fn enclose(&self, index: usize) -> Option<usize> {
+        if index >= self.len || !self.is_open(index) { return None; }
+        self.backward_search(index, self.excess(index) - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_bp(parens: &[bool]) {
+        let mut bits = BitVector::new();
+        for p in parens { bits.push(*p); }
+        let bp = BalancedParens::new(bits);
+        assert_eq!(bp.len(), parens.len());
+        let mut stack = Vec::new();
+        let mut close = vec![None; parens.len()];
+        let mut open = vec![None; parens.len()];
+        let mut enclose = vec![None; parens.len()];
+        for (i, p) in parens.iter().enumerate() {
+            if *p {
+                enclose[i] = stack.last().copied();
+                stack.push(i);
+            } else {
+                let o = stack.pop().unwrap();
+                close[o] = Some(i);
+                open[i] = Some(o);
+            }
+        }
+        for i in 0..parens.len() {
+            assert_eq!(bp.find_close(i), close[i], "find_close({})", i);
+            assert_eq!(bp.find_open(i), open[i], "find_open({})", i);
+            assert_eq!(bp.enclose(i), enclose[i], "enclose({})", i);
+        }
+        assert_eq!(bp.excess(parens.len()), 0);
+    }
+
+    #[test]
+    fn small() {
+        test_bp(&[]);
+        test_bp(&[true, false]);
+        test_bp(&[true, true, false, true, true, false, false, false, true, false]);
+    }
+
+    #[test]
+    fn deep_and_shallow() {
+        let mut parens = vec![true; 40000];
+        parens.resize(80000, false);
+        for _ in 0..20000 { parens.extend([true, false]); }
+        test_bp(&parens);
+    }
+
+    #[test]
+    fn byte_excess() {
+        assert_eq!(BYTE_EXCESS[0], (-8, -8, -7));
+        assert_eq!(BYTE_EXCESS[0xFF], (8, 1, 0));
+        assert_eq!(BYTE_EXCESS[0b0101_0110], (0, -1, -1));   // )(()()()
+    }
+
+    #[test]
+    fn many_superblocks() {    // deep nesting that spans many superblocks, which checks the min-tree
+        let mut parens = Vec::new();
+        for depth in [70000, 3, 100000, 1] {
+            parens.extend(std::iter::repeat_n(true, depth));
+            parens.extend(std::iter::repeat_n(false, depth));
+        }
+        parens.push(true);
+        parens.extend(std::iter::repeat_n([true, false], 30000).flatten());
+        parens.push(false);
+        test_bp(&parens);
+    }
+
+    #[test]
+    fn pseudo_random() {
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        let mut parens = Vec::new();
+        let mut depth = 0;
+        for _ in 0..80000 {
+            x ^= x << 13; x ^= x >> 7; x ^= x << 17;
+            let open = depth == 0 || x & 1 == 0;
+            depth = if open { depth + 1 } else { depth - 1 };
+            parens.push(open);
+        }
+        parens.resize(parens.len() + depth, false);
+        test_bp(&parens);
+    }
+}
@@ -0,0 +1,432 @@
+use std::iter::FusedIterator;
+use std::ops::{Bound, Deref, DerefMut, Index, RangeBounds};
+use dyn_size_of::GetSize;
+use super::{BitAccess, BitOnesIterator, ceiling_div, n_lowest_bits};
+
+/// Growable, owned vector of bits that knows its length (in bits).
+///
+/// The bits are stored in the vector of `u64` words to which `BitVector` dereferences,
+/// so all [`BitAccess`] methods can be used with it.
+/// The bits of the last word beyond the length are always cleared
+/// (if they are set by [`BitAccess`] methods, the results of some `BitVector` methods are unspecified).
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct BitVector {
+    words: Vec<u64>,
+    len: usize
+}
+
+impl GetSize for BitVector {
+    #[inline] fn size_bytes_dyn(&self) -> usize { self.words.size_bytes_dyn() }
+    #[inline] fn size_bytes_content_dyn(&self) -> usize { self.words.size_bytes_content_dyn() }
+    const USES_DYN_MEM: bool = true;
+}
+
+impl BitVector {
+    /// Constructs an empty vector.
+    #[inline] pub fn new() -> Self { Self::default() }
+
+    /// Constructs an empty vector which can store at least `bit_capacity` bits without reallocation.
+    #[inline] pub fn with_capacity(bit_capacity: usize) -> Self {
+        Self { words: Vec::with_capacity(ceiling_div(bit_capacity, 64)), len: 0 }
+    }
+
+    /// Constructs a vector of `len` bits, all set to `0`.
+    #[inline] pub fn with_zeroed_bits(len: usize) -> Self {
+        Self { words: vec![0; ceiling_div(len, 64)], len }
+    }
+
+    /// Constructs a vector of `len` bits, all set to `1`.
+    pub fn with_filled_bits(len: usize) -> Self {
+        let mut result = Self { words: vec![u64::MAX; ceiling_div(len, 64)], len };
+        result.clear_tail();
+        result
+    }
+
+    /// Constructs a vector of the first `len` bits of `words`. The rest bits of `words` are cleared.
+    /// Panics if `words` contain less than `len` bits.
+    pub fn from_words(words: impl Into<Vec<u64>>, len: usize) -> Self {
+        let mut words = words.into();
+        assert!(len <= words.len() * 64, "BitVector::from_words: too few words for {} bits", len);
+        words.truncate(ceiling_div(len, 64));
+        let mut result = Self { words, len };
+        result.clear_tail();
+        result
+    }
+
+    /// Clears the bits of the last word that are beyond the length.
+    #[inline] fn clear_tail(&mut self) {
+        let tail = (self.len % 64) as u8;
+        if tail != 0 { *self.words.last_mut().unwrap() &= n_lowest_bits(tail); }
+    }
+
+    /// Returns the number of bits in the vector.
+    #[inline] pub fn len(&self) -> usize { self.len }
+
+    /// Returns whether the vector contains no bits.
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns the number of bits the vector can hold without reallocation.
+    #[inline] pub fn capacity(&self) -> usize { self.words.capacity() * 64 }
+
+    /// Returns the words that store the bits of the vector.
+    #[inline] pub fn words(&self) -> &[u64] { &self.words }
+
+    /// Returns the words that store the bits of the vector, consuming `self`.
+    #[inline] pub fn into_words(self) -> Box<[u64]> { self.words.into_boxed_slice() }
+
+    /// Returns the bit with given `index` or `None` if `index` is out of bounds.
+    #[inline] pub fn get(&self, index: usize) -> Option<bool> {
+        (index < self.len).then(|| self.words.get_bit(index))
+    }
+
+    /// Sets the bit with given `index` to `value`. Panics if `index` is out of bounds.
+    #[inline] pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < self.len, "BitVector::set: index {} is out of bounds (length is {})", index, self.len);
+        if value { self.words.set_bit(index) } else { self.words.clear_bit(index) }
+    }
+
+    /// Appends the `bit` to the end of the vector.
+    #[inline] pub fn push(&mut self, bit: bool) {
+        if self.len == self.words.len() * 64 { self.words.push(0); }
+        if bit { self.words.set_bit(self.len); }
+        self.len += 1;
+    }
+
+    /// Appends `len` (at most 64) lowest bits of `bits` to the end of the vector.
+    pub fn push_bits(&mut self, bits: u64, len: u8) {
+        debug_assert!(len <= 64);
+        if len == 0 { return; }
+        let bits = if len == 64 { bits } else { bits & n_lowest_bits(len) };
+        let new_len = self.len + len as usize;
+        self.words.resize(ceiling_div(new_len, 64), 0);
+        let offset = self.len % 64;
+        let index = self.len / 64;
+        self.words[index] |= bits << offset;
+        if offset + len as usize > 64 { self.words[index + 1] |= bits >> (64 - offset); }
+        self.len = new_len;
+    }
+
+    /// Removes the last bit from the vector and returns it, or `None` if the vector is empty.
+    pub fn pop(&mut self) -> Option<bool> {
+        let index = self.len.checked_sub(1)?;
+        let result = self.words.get_bit(index);
+        self.truncate(index);
+        Some(result)
+    }
+
+    /// Shortens the vector to `len` bits. Does nothing if `len` is not less than the current length.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len { return; }
+        self.words.truncate(ceiling_div(len, 64));
+        self.len = len;
+        self.clear_tail();
+    }
+
+    /// Inserts the `bit` at position `index`, shifting all bits after it to the right.
+    /// Takes *O(len/64)* time. Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, bit: bool) {
+        assert!(index <= self.len, "BitVector::insert: index {} is out of bounds (length is {})", index, self.len);
+        self.push(false);
+        let first = index / 64;
+        for w in (first+1..self.words.len()).rev() {
+            self.words[w] = (self.words[w] << 1) | (self.words[w-1] >> 63);
+        }
+        let word = self.words[first];
+        let low = n_lowest_bits((index % 64) as u8);
+        self.words[first] = (word & low) | ((word & !low) << 1) | ((bit as u64) << (index % 64));
+    }
+
+    /// Removes and returns the bit at position `index`, shifting all bits after it to the left.
+    /// Takes *O(len/64)* time. Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> bool {
+        assert!(index < self.len, "BitVector::remove: index {} is out of bounds (length is {})", index, self.len);
+        let result = self.words.get_bit(index);
+        let first = index / 64;
+        let word = self.words[first];
+        let low = n_lowest_bits((index % 64) as u8);
+        self.words[first] = (word & low) | ((word >> 1) & !low);
+        for w in first+1..self.words.len() {
+            self.words[w-1] |= self.words[w] << 63;
+            self.words[w] >>= 1;
+        }
+        self.truncate(self.len - 1);
+        result
+    }
+
+    /// Appends all bits of `other` to the end of the vector.
+    pub fn extend_from_bits(&mut self, other: &BitVector) {
+        let offset = self.len % 64;
+        if offset == 0 {
+            self.words.extend_from_slice(&other.words);
+            self.len += other.len;
+        } else {
+            for (i, word) in other.words.iter().enumerate() {
+                self.push_bits(*word, (other.len - i * 64).min(64) as u8);
+            }
+        }
+    }
+
+    /// Removes all bits from the vector.
+    #[inline] pub fn clear(&mut self) {
+        self.words.clear();
+        self.len = 0;
+    }
+
+    /// Returns a new vector that contains the copy of bits of `self` from given `range`.
+    /// Panics if the `range` is out of bounds.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let begin = match range.start_bound() {
+            Bound::Included(b) => *b,
+            Bound::Excluded(b) => b + 1,
+            Bound::Unbounded => 0
+        };
+        let end = match range.end_bound() {
+            Bound::Included(e) => e + 1,
+            Bound::Excluded(e) => *e,
+            Bound::Unbounded => self.len
+        };
+        assert!(begin <= end && end <= self.len, "BitVector::slice: range {}..{} is out of bounds (length is {})", begin, end, self.len);
+        let mut result = Self::with_capacity(end - begin);
+        let mut i = begin;
+        while i < end {
+            let len = (end - i).min(63) as u8;
+            result.push_bits(self.words.get_bits(i, len), len);
+            i += len as usize;
+        }
+        result
+    }
+
+    /// Returns the number of ones (set bits) in the vector.
+    #[inline] pub fn count_ones(&self) -> usize { self.words.count_bit_ones() }
+
+    /// Returns the number of zeros (cleared bits) in the vector.
+    #[inline] pub fn count_zeros(&self) -> usize { self.len - self.count_ones() }
+
+    /// Returns iterator over all bits of the vector.
+    #[inline] pub fn iter(&self) -> BitVectorIterator<'_> {
+        BitVectorIterator { words: &self.words, index: 0, end: self.len }
+    }
+
+    /// Returns iterator over indices of ones (set bits) in the vector.
+    #[inline] pub fn ones(&self) -> BitOnesIterator<'_> { self.words.bit_ones() }
+
+    /// Returns iterator over indices of zeros (cleared bits) in the vector.
+    #[inline] pub fn zeros(&self) -> BitZerosIterator<'_> { BitZerosIterator::new(&self.words, self.len) }
+}
+
+impl Deref for BitVector {
+    type Target = [u64];
+    #[inline] fn deref(&self) -> &Self::Target { &self.words }
+}
+
+impl DerefMut for BitVector {
+    #[inline] fn deref_mut(&mut self) -> &mut Self::Target { &mut self.words }
+}
+
+impl Index<usize> for BitVector {
+    type Output = bool;
+
+    /// Returns the bit with given `index`. Panics if `index` is out of bounds.
+    #[inline] fn index(&self, index: usize) -> &Self::Output {
+        assert!(index < self.len, "BitVector: index {} is out of bounds (length is {})", index, self.len);
+        if self.words.get_bit(index) { &true } else { &false }
+    }
+}
+
+impl Extend<bool> for BitVector {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.words.reserve(ceiling_div(self.len + iter.size_hint().0, 64) - self.words.len());
+        for bit in iter { self.push(bit); }
+    }
+}
+
+impl<'a> Extend<&'a bool> for BitVector {
+    #[inline] fn extend<I: IntoIterator<Item = &'a bool>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+impl FromIterator<bool> for BitVector {
+    #[inline] fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut result = Self::new();
+        result.extend(iter);
+        result
+    }
+}
+
+impl From<BitVector> for Box<[u64]> {
+    #[inline] fn from(bits: BitVector) -> Self { bits.into_words() }
+}
+
+impl<'a> IntoIterator for &'a BitVector {
+    type Item = bool;
+    type IntoIter = BitVectorIterator<'a>;
+    #[inline] fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+/// Iterator over all bits of [`BitVector`].
+pub struct BitVectorIterator<'a> {
+    words: &'a [u64],
+    index: usize,
+    end: usize
+}
+
+impl<'a> Iterator for BitVectorIterator<'a> {
+    type Item = bool;
+
+    #[inline] fn next(&mut self) -> Option<Self::Item> {
+        (self.index < self.end).then(|| {
+            self.index += 1;
+            self.words.get_bit(self.index - 1)
+        })
+    }
+
+    #[inline] fn size_hint(&self) -> (usize, Option<usize>) {
+        let result = self.len();
+        (result, Some(result))
+    }
+}
+
+impl<'a> DoubleEndedIterator for BitVectorIterator<'a> {
+    #[inline] fn next_back(&mut self) -> Option<Self::Item> {
+        (self.index < self.end).then(|| {
+            self.end -= 1;
+            self.words.get_bit(self.end)
+        })
+    }
+}
+
+impl<'a> ExactSizeIterator for BitVectorIterator<'a> {
+    #[inline] fn len(&self) -> usize { self.end - self.index }
+}
+
+impl<'a> FusedIterator for BitVectorIterator<'a> {}
+
+/// Iterator over indices of zeros (cleared bits) among the first `len` bits of slice of `u64`.
+pub struct BitZerosIterator<'a> {
+    segment_iter: std::slice::Iter<'a, u64>,
+    first_segment_bit: usize,
+    current_segment: u64,   // negated segment
+    len: usize
+}
+
+impl<'a> BitZerosIterator<'a> {
+    /// Constructs iterator over zeros among the first `len` bits of the given `slice`.
+    pub fn new(slice: &'a [u64], len: usize) -> Self {
+        let mut segment_iter = slice[..ceiling_div(len, 64)].iter();
+        let current_segment = segment_iter.next().map_or(0, |s| !*s);
+        Self { segment_iter, first_segment_bit: 0, current_segment, len }
+    }
+}
+
+impl<'a> Iterator for BitZerosIterator<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current_segment == 0 {
+            self.current_segment = !*self.segment_iter.next()?;
+            self.first_segment_bit += 64;
+        }
+        let result = self.first_segment_bit + self.current_segment.trailing_zeros() as usize;
+        if result >= self.len {
+            self.current_segment = 0;
+            return None;
+        }
+        self.current_segment &= self.current_segment - 1;
+        Some(result)
+    }
+}
+
+impl<'a> FusedIterator for BitZerosIterator<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_pop_truncate() {
+        let mut v = BitVector::new();
+        assert!(v.is_empty());
+        for i in 0..150 { v.push(i % 3 == 0); }
+        assert_eq!(v.len(), 150);
+        assert_eq!(v.words().len(), 3);
+        assert_eq!(v.count_ones(), 50);
+        assert_eq!(v.count_zeros(), 100);
+        assert!(v[0]);
+        assert!(!v[1]);
+        assert!(v[147]);
+        assert_eq!(v.get(149), Some(false));
+        assert_eq!(v.get(150), None);
+        assert_eq!(v.pop(), Some(false));
+        assert_eq!(v.len(), 149);
+        v.truncate(65);
+        assert_eq!(v.len(), 65);
+        assert_eq!(v.words().len(), 2);
+        assert_eq!(v.words()[1], 0);
+        assert_eq!(v.count_ones(), 22);
+        v.set(64, true);
+        assert_eq!(v.words()[1], 1);
+        assert!(v.get_bit(64));  // BitAccess through Deref
+    }
+
+    #[test]
+    fn extend_and_iterators() {
+        let bits = [true, false, false, true, true, false, true];
+        let mut v: BitVector = bits.iter().copied().collect();
+        assert_eq!(v.iter().collect::<Vec<_>>(), bits);
+        assert_eq!(v.iter().rev().collect::<Vec<_>>(), bits.iter().rev().copied().collect::<Vec<_>>());
+        assert_eq!(v.ones().collect::<Vec<_>>(), [0, 3, 4, 6]);
+        assert_eq!(v.zeros().collect::<Vec<_>>(), [1, 2, 5]);
+        v.extend(&[false; 64]);
+        assert_eq!(v.len(), 71);
+        assert_eq!(v.zeros().count(), 67);
+        assert_eq!(v.zeros().last(), Some(70));
+        assert_eq!(v.ones().count(), 4);
+    }
+
+    #[test]
+    fn filled_and_slice() {
+        let v = BitVector::with_filled_bits(100);
+        assert_eq!(v.count_ones(), 100);
+        assert_eq!(v.words()[1], n_lowest_bits(36));
+        assert_eq!(v.zeros().next(), None);
+        let mut v = BitVector::with_zeroed_bits(200);
+        for i in (0..200).step_by(7) { v.set_bit(i); }
+        let s = v.slice(5..190);
+        assert_eq!(s.len(), 185);
+        assert_eq!(s.iter().collect::<Vec<_>>(), v.iter().skip(5).take(185).collect::<Vec<_>>());
+        assert_eq!(v.slice(..), v);
+        assert!(v.slice(10..10).is_empty());
+    }
+
+    #[test]
+    fn insert_remove_extend() {
+        let mut v = BitVector::new();
+        let mut expected = Vec::new();
+        for i in 0..300 {
+            let index = (i * 37) % (expected.len() + 1);
+            v.insert(index, i % 3 == 0);
+            expected.insert(index, i % 3 == 0);
+        }
+        assert_eq!(v.iter().collect::<Vec<_>>(), expected);
+        for i in 0..100 {
+            let index = (i * 53) % expected.len();
+            assert_eq!(v.remove(index), expected.remove(index));
+        }
+        assert_eq!(v.iter().collect::<Vec<_>>(), expected);
+        assert_eq!(v, expected.iter().copied().collect());
+        let w: BitVector = (0..70).map(|i| i % 5 == 0).collect();
+        v.extend_from_bits(&w);
+        expected.extend(w.iter());
+        assert_eq!(v, expected.iter().copied().collect());
+    }
+
+    #[test]
+    fn from_words() {
+        let v = BitVector::from_words(vec![u64::MAX, u64::MAX, 1], 70);
+        assert_eq!(v.len(), 70);
+        assert_eq!(v.words(), [u64::MAX, 0b111111]);
+        assert_eq!(Box::<[u64]>::from(v).as_ref(), [u64::MAX, 0b111111]);
+    }
+}
@@ -1,11 +1,13 @@
 use std::iter::FusedIterator;
+use std::ops::Range;
 use super::{ceiling_div, n_lowest_bits};
 
 /// Iterator over bits set to one in slice of `u64`.
 pub struct BitOnesIterator<'a> {
     segment_iter: std::slice::Iter<'a, u64>,
     first_segment_bit: usize,
-    current_segment: u64
+    current_segment: u64,
+    last_segment: u64   // (masked) segment to visit after the segments of segment_iter
 }
 
 impl<'a> BitOnesIterator<'a> {
@@ -16,7 +18,28 @@ impl<'a> BitOnesIterator<'a> {
         Self {
             segment_iter,
             first_segment_bit: 0,
-            current_segment
+            current_segment,
+            last_segment: 0
+        }
+    }
+
+    /// Constructs iterator over bits set in the given `slice`, with indices in the given `range`.
+    pub fn with_range(slice: &'a [u64], range: Range<usize>) -> Self {
+        let end = range.end.min(slice.len() * 64);
+        if range.start >= end { return Self::new(&[]); }
+        let (first, last) = (range.start / 64, (end - 1) / 64);
+        let first_mask = u64::MAX << (range.start % 64);
+        let last_mask = u64::MAX >> (63 - (end - 1) % 64);
+        let (current_segment, last_segment) = if first == last {
+            (slice[first] & first_mask & last_mask, 0)
+        } else {
+            (slice[first] & first_mask, slice[last] & last_mask)
+        };
+        Self {
+            segment_iter: slice[first+1..last.max(first+1)].iter(),
+            first_segment_bit: first * 64,
+            current_segment,
+            last_segment
         }
     }
 }
@@ -26,7 +49,11 @@ impl<'a> Iterator for BitOnesIterator<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         while self.current_segment == 0 {
-            self.current_segment = *self.segment_iter.next()?;
+            self.current_segment = match self.segment_iter.next() {
+                Some(segment) => *segment,
+                None if self.last_segment != 0 => std::mem::take(&mut self.last_segment),
+                None => return None
+            };
             self.first_segment_bit += 64;
         }
         let result = self.current_segment.trailing_zeros();
@@ -43,6 +70,7 @@ impl<'a> Iterator for BitOnesIterator<'a> {
 impl<'a> ExactSizeIterator for BitOnesIterator<'a> {
     #[inline] fn len(&self) -> usize {
         self.current_segment.count_ones() as usize + self.segment_iter.as_slice().count_bit_ones()
+            + self.last_segment.count_ones() as usize
     }
 }
 
@@ -78,6 +106,70 @@ pub trait BitAccess {
     /// Returns iterator over indices of ones (set bits).
     fn bit_ones(&self) -> BitOnesIterator;
 
+    /// Returns iterator over indices of ones (set bits) that are not less than `from`.
+    #[inline] fn bit_ones_from(&self, from: usize) -> BitOnesIterator<'_> {
+        self.bit_ones_in(from..usize::MAX)
+    }
+
+    /// Returns iterator over indices of ones (set bits) in the given `range`.
+    fn bit_ones_in(&self, range: Range<usize>) -> BitOnesIterator<'_>;
+
+    /// Returns the index of the first one (set bit) which is not less than `from`,
+    /// or `None` if there is no such one.
+    fn next_one(&self, from: usize) -> Option<usize>;
+
+    /// Returns the index of the last one (set bit) which is not greater than `from`,
+    /// or `None` if there is no such one.
+    fn prev_one(&self, from: usize) -> Option<usize>;
+
+    /// Returns the index of the first zero (cleared bit) which is not less than `from`,
+    /// or `None` if there is no such zero.
+    fn next_zero(&self, from: usize) -> Option<usize>;
+
+    /// Returns the index of the last zero (cleared bit) which is not greater than `from`,
+    /// or `None` if there is no such zero.
+    fn prev_zero(&self, from: usize) -> Option<usize>;
+
+    /// Sets all bits with indices in the given `range` to `1`.
+    fn set_range(&mut self, range: Range<usize>);
+
+    /// Sets all bits with indices in the given `range` to `0`.
+    fn clear_range(&mut self, range: Range<usize>);
+
+    /// Replaces `self` with bitwise `self & other`. Panics if `other` has different length than `self`.
+    fn bit_and_assign(&mut self, other: &Self);
+
+    /// Replaces `self` with bitwise `self | other`. Panics if `other` has different length than `self`.
+    fn bit_or_assign(&mut self, other: &Self);
+
+    /// Replaces `self` with bitwise `self ^ other`. Panics if `other` has different length than `self`.
+    fn bit_xor_assign(&mut self, other: &Self);
+
+    /// Replaces `self` with bitwise `self & !other`. Panics if `other` has different length than `self`.
+    fn bit_and_not_assign(&mut self, other: &Self);
+
+    /// Negates all bits of `self`.
+    fn bit_not_assign(&mut self);
+
+    /// Returns bitwise `self & other`. Panics if `other` has different length than `self`.
+    fn bit_and(&self, other: &Self) -> Box<[u64]>;
+
+    /// Returns bitwise `self | other`. Panics if `other` has different length than `self`.
+    fn bit_or(&self, other: &Self) -> Box<[u64]>;
+
+    /// Returns bitwise `self ^ other`. Panics if `other` has different length than `self`.
+    fn bit_xor(&self, other: &Self) -> Box<[u64]>;
+
+    /// Returns bitwise `self & !other`. Panics if `other` has different length than `self`.
+    fn bit_and_not(&self, other: &Self) -> Box<[u64]>;
+
+    /// Returns bitwise negation of `self`.
+    fn bit_not(&self) -> Box<[u64]>;
+
+    /// Returns the number of ones in bitwise `self & other` (the size of the intersection),
+    /// without constructing `self & other`. Panics if `other` has different length than `self`.
+    fn count_bit_ones_and(&self, other: &Self) -> usize;
+
     /// Gets `v_size` bits with indices in range [`index*v_size`, `index*v_size+v_size`).
     #[inline(always)] fn get_fragment(&self, index: usize, v_size: u8) -> u64 {
         self.get_bits(index * v_size as usize, v_size)
@@ -221,6 +313,62 @@ pub fn bitvec_with_items<V: Into<u64>, I: IntoIterator<Item=V>>(items: I, fragme
     result
 }*/
 
+/// Returns the index of the first one (if `ONE` is `true`) or zero (otherwise) which is not less than `from`
+/// and is in one of the words of `content` with indices less than `end_word` (which cannot exceed `content.len()`).
+pub(crate) fn next_bit<const ONE: bool>(content: &[u64], from: usize, end_word: usize) -> Option<usize> {
+    let mut word_index = from / 64;
+    if word_index >= end_word { return None; }
+    let mut word = (if ONE { content[word_index] } else { !content[word_index] }) & (u64::MAX << (from % 64));
+    while word == 0 {
+        word_index += 1;
+        if word_index >= end_word { return None; }
+        word = if ONE { content[word_index] } else { !content[word_index] };
+    }
+    Some(word_index * 64 + word.trailing_zeros() as usize)
+}
+
+/// Returns the index of the last one (if `ONE` is `true`) or zero (otherwise) which is not greater than `from`
+/// (that must be less than `64 * content.len()`)
+/// and is in one of the words of `content` with indices not less than `begin_word`.
+pub(crate) fn prev_bit<const ONE: bool>(content: &[u64], from: usize, begin_word: usize) -> Option<usize> {
+    let mut word_index = from / 64;
+    let mut word = (if ONE { content[word_index] } else { !content[word_index] }) & (u64::MAX >> (63 - from % 64));
+    while word == 0 {
+        if word_index <= begin_word { return None; }
+        word_index -= 1;
+        word = if ONE { content[word_index] } else { !content[word_index] };
+    }
+    Some(word_index * 64 + 63 - word.leading_zeros() as usize)
+}
+
+/// Replaces each word `a` of `target` with `op(a, b)`, where `b` is the corresponding word of `other`.
+#[inline(always)] fn zip_assign<Op: Fn(u64, u64) -> u64>(target: &mut [u64], other: &[u64], op: Op) {
+    assert_eq!(target.len(), other.len(), "bit arrays must have the same length");
+    for (a, b) in target.iter_mut().zip(other.iter()) { *a = op(*a, *b); }
+}
+
+/// Returns array of `op(a, b)` for subsequent words `a` and `b` of `first` and `second`.
+#[inline(always)] fn zip_collect<Op: Fn(u64, u64) -> u64>(first: &[u64], second: &[u64], op: Op) -> Box<[u64]> {
+    assert_eq!(first.len(), second.len(), "bit arrays must have the same length");
+    first.iter().zip(second.iter()).map(|(a, b)| op(*a, *b)).collect()
+}
+
+/// Calls `change(word, mask)` for each word of `array` that contains bits in `range`,
+/// where `mask` has ones exactly at the positions of these bits.
+#[inline(always)] fn change_bit_range<F: Fn(&mut u64, u64)>(array: &mut [u64], range: Range<usize>, change: F) {
+    if range.start >= range.end { return; }
+    let (first, last) = (range.start / 64, (range.end - 1) / 64);
+    let first_mask = u64::MAX << (range.start % 64);
+    let last_mask = u64::MAX >> (63 - (range.end - 1) % 64);
+    if first == last {
+        change(&mut array[first], first_mask & last_mask);
+    } else {
+        change(&mut array[first], first_mask);
+        for w in array[first+1..last].iter_mut() { change(w, u64::MAX); }
+        change(&mut array[last], last_mask);
+    }
+}
+
 impl BitAccess for [u64] {
     #[inline(always)] fn get_bit(&self, bit_nr: usize) -> bool {
         self[bit_nr / 64] & (1u64 << (bit_nr % 64) as u64) != 0
@@ -246,6 +394,79 @@ impl BitAccess for [u64] {
         BitOnesIterator::new(self)
     }
 
+    #[inline] fn bit_ones_in(&self, range: Range<usize>) -> BitOnesIterator<'_> {
+        BitOnesIterator::with_range(self, range)
+    }
+
+    #[inline] fn next_one(&self, from: usize) -> Option<usize> {
+        next_bit::<true>(self, from, self.len())
+    }
+
+    #[inline] fn prev_one(&self, from: usize) -> Option<usize> {
+        prev_bit::<true>(self, from.min((self.len() * 64).checked_sub(1)?), 0)
+    }
+
+    #[inline] fn next_zero(&self, from: usize) -> Option<usize> {
+        next_bit::<false>(self, from, self.len())
+    }
+
+    #[inline] fn prev_zero(&self, from: usize) -> Option<usize> {
+        prev_bit::<false>(self, from.min((self.len() * 64).checked_sub(1)?), 0)
+    }
+
+    #[inline] fn set_range(&mut self, range: Range<usize>) {
+        change_bit_range(self, range, |w, mask| *w |= mask);
+    }
+
+    #[inline] fn clear_range(&mut self, range: Range<usize>) {
+        change_bit_range(self, range, |w, mask| *w &= !mask);
+    }
+
+    #[inline] fn bit_and_assign(&mut self, other: &Self) {
+        zip_assign(self, other, |a, b| a & b)
+    }
+
+    #[inline] fn bit_or_assign(&mut self, other: &Self) {
+        zip_assign(self, other, |a, b| a | b)
+    }
+
+    #[inline] fn bit_xor_assign(&mut self, other: &Self) {
+        zip_assign(self, other, |a, b| a ^ b)
+    }
+
+    #[inline] fn bit_and_not_assign(&mut self, other: &Self) {
+        zip_assign(self, other, |a, b| a & !b)
+    }
+
+    fn bit_not_assign(&mut self) {
+        for w in self.iter_mut() { *w = !*w; }
+    }
+
+    #[inline] fn bit_and(&self, other: &Self) -> Box<[u64]> {
+        zip_collect(self, other, |a, b| a & b)
+    }
+
+    #[inline] fn bit_or(&self, other: &Self) -> Box<[u64]> {
+        zip_collect(self, other, |a, b| a | b)
+    }
+
+    #[inline] fn bit_xor(&self, other: &Self) -> Box<[u64]> {
+        zip_collect(self, other, |a, b| a ^ b)
+    }
+
+    #[inline] fn bit_and_not(&self, other: &Self) -> Box<[u64]> {
+        zip_collect(self, other, |a, b| a & !b)
+    }
+
+    fn bit_not(&self) -> Box<[u64]> {
+        self.iter().map(|w| !w).collect()
+    }
+
+    fn count_bit_ones_and(&self, other: &Self) -> usize {
+        assert_eq!(self.len(), other.len(), "bit arrays must have the same length");
+        self.iter().zip(other.iter()).map(|(a, b)| (a & b).count_ones() as usize).sum()
+    }
+
     fn get_bits(&self, begin: usize, len: u8) -> u64 {
         let index_segment = begin / 64;
         //data += index_bit / 64;
@@ -459,6 +680,89 @@ mod tests {
         assert!(b.get_bit(74));
     }
 
+    #[test]
+    fn ranges() {
+        let mut b = Box::<[u64]>::with_zeroed_64bit_segments(3);
+        b.set_range(3..7);
+        assert_eq!(b.as_ref(), [0b1111000, 0, 0]);
+        b.set_range(60..130);
+        assert_eq!(b.as_ref(), [0b1111u64 << 60 | 0b1111000, u64::MAX, 0b11]);
+        b.clear_range(4..128);
+        assert_eq!(b.as_ref(), [0b1000, 0, 0b11]);
+        b.set_range(5..5);
+        b.clear_range(129..129);
+        assert_eq!(b.as_ref(), [0b1000, 0, 0b11]);
+        b.set_range(0..192);
+        assert_eq!(b.count_bit_ones(), 192);
+        b.clear_range(64..128);
+        assert_eq!(b.as_ref(), [u64::MAX, 0, u64::MAX]);
+    }
+
+    #[test]
+    fn bulk_operations() {
+        let a = [0b1100u64, u64::MAX, 0];
+        let b = [0b1010u64, 1, u64::MAX];
+        assert_eq!(a.bit_and(&b).as_ref(), [0b1000, 1, 0]);
+        assert_eq!(a.bit_or(&b).as_ref(), [0b1110, u64::MAX, u64::MAX]);
+        assert_eq!(a.bit_xor(&b).as_ref(), [0b0110, u64::MAX-1, u64::MAX]);
+        assert_eq!(a.bit_and_not(&b).as_ref(), [0b0100, u64::MAX-1, 0]);
+        assert_eq!(a.bit_not().as_ref(), [!0b1100, 0, u64::MAX]);
+        assert_eq!(a.count_bit_ones_and(&b), 2);
+        let mut c = a;
+        c.bit_and_assign(&b);
+        assert_eq!(c, [0b1000, 1, 0]);
+        c.bit_or_assign(&a);
+        assert_eq!(c, a);
+        c.bit_xor_assign(&b);
+        assert_eq!(c, [0b0110, u64::MAX-1, u64::MAX]);
+        c.bit_and_not_assign(&a);
+        assert_eq!(c, [0b0010, 0, u64::MAX]);
+        c.bit_not_assign();
+        assert_eq!(c, [!0b0010, u64::MAX, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bulk_operations_different_lengths() {
+        [1u64, 2].bit_and(&[1]);
+    }
+
+    #[test]
+    fn next_prev() {
+        let b = [0b11u64 << 62, 0, 1 << 5, u64::MAX];
+        assert_eq!(b.next_one(0), Some(62));
+        assert_eq!(b.next_one(63), Some(63));
+        assert_eq!(b.next_one(64), Some(128+5));
+        assert_eq!(b.next_one(128+6), Some(192));
+        assert_eq!(b.next_one(256), None);
+        assert_eq!(b.prev_one(128+4), Some(63));
+        assert_eq!(b.prev_one(61), None);
+        assert_eq!(b.prev_one(1000), Some(255));
+        assert_eq!(b.next_zero(192), None);
+        assert_eq!(b.next_zero(62), Some(64));
+        assert_eq!(b.next_zero(66), Some(66));
+        assert_eq!(b.prev_zero(1000), Some(191));
+        assert_eq!(b.prev_zero(128+5), Some(128+4));
+        assert_eq!(b.prev_zero(0), Some(0));
+        assert_eq!([0u64; 0].prev_one(5), None);
+        assert_eq!([0u64; 0].next_zero(0), None);
+    }
+
+    #[test]
+    fn iterators_in_range() {
+        let b = [0b1001u64 << 62 | 0b1010, u64::MAX, 0, 1 << 5];
+        let all: Vec<_> = b.bit_ones().collect();
+        for begin in 0..260 {
+            assert_eq!(b.bit_ones_from(begin).collect::<Vec<_>>(), all.iter().copied().filter(|i| *i >= begin).collect::<Vec<_>>());
+            for end in [begin, begin+1, begin+63, begin+64, begin+65, begin+129, 300] {
+                let expected: Vec<_> = all.iter().copied().filter(|i| (begin..end).contains(i)).collect();
+                let iter = b.bit_ones_in(begin..end);
+                assert_eq!(iter.len(), expected.len());
+                assert_eq!(iter.collect::<Vec<_>>(), expected, "range {}..{}", begin, end);
+            }
+        }
+    }
+
     #[test]
     fn iterators() {
         let b = [0b101u64, 0b10u64];
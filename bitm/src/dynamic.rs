@@ -0,0 +1,416 @@
+use dyn_size_of::GetSize;
+use super::{BitVector, BitArrayWithRank, select64, n_lowest_bits};
+
+/// Maximum number of bits in a leaf.
+const LEAF_BITS: usize = 16 * 64;
+
+/// Maximum number of children of an internal node.
+const MAX_CHILDREN: usize = 16;
+
+/// Content of [`Node`].
+#[derive(Clone)]
+enum NodeContent {
+    Leaf(BitVector),
+    Internal(Vec<Node>)
+}
+
+/// Node of [`DynamicBitVector`] that stores the counts of bits and ones in its subtree.
+#[derive(Clone)]
+struct Node {
+    len: usize,
+    ones: usize,
+    content: NodeContent
+}
+
+impl GetSize for Node {
+    fn size_bytes_dyn(&self) -> usize {
+        match &self.content {
+            NodeContent::Leaf(bits) => bits.size_bytes_dyn(),
+            NodeContent::Internal(children) => children.size_bytes_dyn()
+        }
+    }
+    const USES_DYN_MEM: bool = true;
+}
+
+impl Node {
+    /// Constructs an empty leaf.
+    #[inline] fn empty_leaf() -> Self {
+        Self { len: 0, ones: 0, content: NodeContent::Leaf(BitVector::new()) }
+    }
+
+    /// Constructs the leaf that stores given `bits`.
+    #[inline] fn leaf(bits: BitVector) -> Self {
+        Self { len: bits.len(), ones: bits.count_ones(), content: NodeContent::Leaf(bits) }
+    }
+
+    /// Constructs the internal node with given `children`.
+    #[inline] fn internal(children: Vec<Node>) -> Self {
+        Self {
+            len: children.iter().map(|c| c.len).sum(),
+            ones: children.iter().map(|c| c.ones).sum(),
+            content: NodeContent::Internal(children)
+        }
+    }
+
+    /// Returns whether the node is too large and should be split.
+    #[inline] fn is_overfull(&self) -> bool {
+        match &self.content {
+            NodeContent::Leaf(bits) => bits.len() > LEAF_BITS,
+            NodeContent::Internal(children) => children.len() > MAX_CHILDREN
+        }
+    }
+
+    /// Returns whether the node is too small and should be merged with its sibling.
+    #[inline] fn is_underfull(&self) -> bool {
+        match &self.content {
+            NodeContent::Leaf(bits) => bits.len() < LEAF_BITS / 4,
+            NodeContent::Internal(children) => children.len() < MAX_CHILDREN / 4
+        }
+    }
+
+    /// Moves the second half of the content of `self` to the returned node.
+    fn split(&mut self) -> Node {
+        let result = match &mut self.content {
+            NodeContent::Leaf(bits) => {
+                let mid = bits.words().len() / 2 * 64;
+                let second = bits.slice(mid..);
+                bits.truncate(mid);
+                Node::leaf(second)
+            }
+            NodeContent::Internal(children) => Node::internal(children.split_off(children.len() / 2))
+        };
+        self.len -= result.len;
+        self.ones -= result.ones;
+        result
+    }
+
+    /// Appends the content of `other` (at the same level of the tree) to `self`.
+    fn merge(&mut self, other: Node) {
+        self.len += other.len;
+        self.ones += other.ones;
+        match (&mut self.content, other.content) {
+            (NodeContent::Leaf(bits), NodeContent::Leaf(other)) => bits.extend_from_bits(&other),
+            (NodeContent::Internal(children), NodeContent::Internal(other)) => children.extend(other),
+            _ => unreachable!("all leaves of DynamicBitVector are at the same level")
+        }
+    }
+
+    /// Returns the bit with given `index`.
+    fn get(&self, mut index: usize) -> bool {
+        match &self.content {
+            NodeContent::Leaf(bits) => bits[index],
+            NodeContent::Internal(children) => {
+                for child in children {
+                    if index < child.len { return child.get(index); }
+                    index -= child.len;
+                }
+                unreachable!()
+            }
+        }
+    }
+
+    /// Sets the bit with given `index` to `value` and returns its previous value.
+    fn set(&mut self, mut index: usize, value: bool) -> bool {
+        let previous = match &mut self.content {
+            NodeContent::Leaf(bits) => {
+                let previous = bits[index];
+                bits.set(index, value);
+                previous
+            }
+            NodeContent::Internal(children) => {
+                let mut children = children.iter_mut();
+                loop {
+                    let child = children.next().unwrap();
+                    if index < child.len { break child.set(index, value); }
+                    index -= child.len;
+                }
+            }
+        };
+        self.ones = self.ones + value as usize - previous as usize;
+        previous
+    }
+
+    /// Inserts the `bit` at position `index`.
+    fn insert(&mut self, mut index: usize, bit: bool) {
+        match &mut self.content {
+            NodeContent::Leaf(bits) => bits.insert(index, bit),
+            NodeContent::Internal(children) => {
+                let mut c = 0;
+                while index > children[c].len {
+                    index -= children[c].len;
+                    c += 1;
+                }
+                children[c].insert(index, bit);
+                if children[c].is_overfull() {
+                    let second = children[c].split();
+                    children.insert(c + 1, second);
+                }
+            }
+        }
+        self.len += 1;
+        self.ones += bit as usize;
+    }
+
+    /// Removes and returns the bit at position `index`.
+    fn remove(&mut self, mut index: usize) -> bool {
+        let result = match &mut self.content {
+            NodeContent::Leaf(bits) => bits.remove(index),
+            NodeContent::Internal(children) => {
+                let mut c = 0;
+                while index >= children[c].len {
+                    index -= children[c].len;
+                    c += 1;
+                }
+                let result = children[c].remove(index);
+                if children[c].is_underfull() && children.len() > 1 {
+                    let first = if c + 1 < children.len() { c } else { c - 1 };
+                    let second = children.remove(first + 1);
+                    children[first].merge(second);
+                    if children[first].is_overfull() {
+                        let second = children[first].split();
+                        children.insert(first + 1, second);
+                    }
+                }
+                result
+            }
+        };
+        self.len -= 1;
+        self.ones -= result as usize;
+        result
+    }
+
+    /// Returns the number of ones in the first `index` bits.
+    fn rank(&self, mut index: usize) -> usize {
+        match &self.content {
+            NodeContent::Leaf(bits) => {
+                let (words, bit) = (bits.words(), (index % 64) as u8);
+                words[..index / 64].iter().map(|w| w.count_ones() as usize).sum::<usize>() +
+                    if bit == 0 { 0 } else { (words[index / 64] & n_lowest_bits(bit)).count_ones() as usize }
+            }
+            NodeContent::Internal(children) => {
+                let mut result = 0;
+                for child in children {
+                    if index <= child.len { return result + child.rank(index); }
+                    index -= child.len;
+                    result += child.ones;
+                }
+                unreachable!()
+            }
+        }
+    }
+
+    /// Returns the position of the `rank`-th one (if `ONE` is `true`) or zero (otherwise),
+    /// which must exist in the subtree.
+    fn select<const ONE: bool>(&self, mut rank: usize) -> usize {
+        match &self.content {
+            NodeContent::Leaf(bits) => {
+                for (i, word) in bits.words().iter().enumerate() {
+                    let word = if ONE { *word } else { !*word };
+                    let count = word.count_ones() as usize;
+                    if rank < count { return i * 64 + select64(word, rank as u8) as usize; }
+                    rank -= count;
+                }
+                unreachable!()
+            }
+            NodeContent::Internal(children) => {
+                let mut position = 0;
+                for child in children {
+                    let count = if ONE { child.ones } else { child.len - child.ones };
+                    if rank < count { return position + child.select::<ONE>(rank); }
+                    rank -= count;
+                    position += child.len;
+                }
+                unreachable!()
+            }
+        }
+    }
+
+    /// Appends the words of the content of the subtree to `result`.
+    fn push_content(&self, result: &mut BitVector) {
+        match &self.content {
+            NodeContent::Leaf(bits) => result.extend_from_bits(bits),
+            NodeContent::Internal(children) => for child in children { child.push_content(result) }
+        }
+    }
+}
+
+/// Dynamic vector of bits that supports `insert`, `remove`, `set`, `rank` and `select` in *O(log n)* time.
+///
+/// It is implemented as a B+-tree whose leaves store up to 1024 bits (16 64-bit words) each,
+/// and each node stores the numbers of bits and ones in its subtree.
+/// It can be frozen into a static structure with faster queries (like [`ArrayWithRank101111`](crate::ArrayWithRank101111))
+/// by the `freeze` method.
+#[derive(Clone)]
+pub struct DynamicBitVector {
+    root: Node
+}
+
+impl GetSize for DynamicBitVector {
+    #[inline] fn size_bytes_dyn(&self) -> usize { self.root.size_bytes_dyn() }
+    const USES_DYN_MEM: bool = true;
+}
+
+impl Default for DynamicBitVector {
+    #[inline] fn default() -> Self { Self { root: Node::empty_leaf() } }
+}
+
+impl DynamicBitVector {
+    /// Constructs an empty vector.
+    #[inline] pub fn new() -> Self { Self::default() }
+
+    /// Returns the number of bits in the vector.
+    #[inline] pub fn len(&self) -> usize { self.root.len }
+
+    /// Returns whether the vector contains no bits.
+    #[inline] pub fn is_empty(&self) -> bool { self.root.len == 0 }
+
+    /// Returns the number of ones in the vector.
+    #[inline] pub fn count_ones(&self) -> usize { self.root.ones }
+
+    /// Returns the number of zeros in the vector.
+    #[inline] pub fn count_zeros(&self) -> usize { self.root.len - self.root.ones }
+
+    /// Returns the bit with given `index` or `None` if `index` is out of bounds.
+    #[inline] pub fn get(&self, index: usize) -> Option<bool> {
+        (index < self.len()).then(|| self.root.get(index))
+    }
+
+    /// Sets the bit with given `index` to `value` and returns its previous value. Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: bool) -> bool {
+        assert!(index < self.len(), "DynamicBitVector::set: index {} is out of bounds (length is {})", index, self.len());
+        self.root.set(index, value)
+    }
+
+    /// Inserts the `bit` at position `index`, shifting all bits after it to the right. Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, bit: bool) {
+        assert!(index <= self.len(), "DynamicBitVector::insert: index {} is out of bounds (length is {})", index, self.len());
+        self.root.insert(index, bit);
+        if self.root.is_overfull() {
+            let second = self.root.split();
+            let first = std::mem::replace(&mut self.root, Node::empty_leaf());
+            self.root = Node::internal(vec![first, second]);
+        }
+    }
+
+    /// Appends the `bit` to the end of the vector.
+    #[inline] pub fn push(&mut self, bit: bool) { self.insert(self.len(), bit) }
+
+    /// Removes and returns the bit at position `index`, shifting all bits after it to the left.
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> bool {
+        assert!(index < self.len(), "DynamicBitVector::remove: index {} is out of bounds (length is {})", index, self.len());
+        let result = self.root.remove(index);
+        if let NodeContent::Internal(children) = &mut self.root.content {
+            if children.len() == 1 { self.root = children.pop().unwrap(); }
+        }
+        result
+    }
+
+    /// Returns the number of ones in the first `index` (at most `self.len()`) bits.
+    #[inline] pub fn rank(&self, index: usize) -> u64 {
+        self.root.rank(index.min(self.len())) as u64
+    }
+
+    /// Returns the position of the `rank`-th one (counting from 0) or `None` if there are no more than `rank` ones.
+    #[inline] pub fn select1(&self, rank: u64) -> Option<usize> {
+        (rank < self.count_ones() as u64).then(|| self.root.select::<true>(rank as usize))
+    }
+
+    /// Returns the position of the `rank`-th zero (counting from 0) or `None` if there are no more than `rank` zeros.
+    #[inline] pub fn select0(&self, rank: u64) -> Option<usize> {
+        (rank < self.count_zeros() as u64).then(|| self.root.select::<false>(rank as usize))
+    }
+
+    /// Returns the bits of the vector.
+    pub fn to_bit_vector(&self) -> BitVector {
+        let mut result = BitVector::with_capacity(self.len());
+        self.root.push_content(&mut result);
+        result
+    }
+
+    /// Returns the static structure `R` (and the number of ones) built over the bits of the vector.
+    #[inline] pub fn freeze<R: BitArrayWithRank>(&self) -> (R, u64) {
+        R::build(self.to_bit_vector().into_words())
+    }
+}
+
+impl Extend<bool> for DynamicBitVector {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        for bit in iter { self.push(bit); }
+    }
+}
+
+impl FromIterator<bool> for DynamicBitVector {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut result = Self::new();
+        result.extend(iter);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArrayWithRank101111;
+
+    fn check(v: &DynamicBitVector, expected: &[bool]) {
+        assert_eq!(v.len(), expected.len());
+        assert_eq!(v.to_bit_vector().iter().collect::<Vec<_>>(), expected);
+        let (mut ones, mut zeros) = (0, 0);
+        for (i, bit) in expected.iter().enumerate() {
+            assert_eq!(v.get(i), Some(*bit));
+            assert_eq!(v.rank(i), ones, "rank({})", i);
+            if *bit {
+                assert_eq!(v.select1(ones), Some(i), "select1({})", ones);
+                ones += 1;
+            } else {
+                assert_eq!(v.select0(zeros), Some(i), "select0({})", zeros);
+                zeros += 1;
+            }
+        }
+        assert_eq!(v.get(expected.len()), None);
+        assert_eq!(v.rank(expected.len()), ones);
+        assert_eq!(v.select1(ones), None);
+        assert_eq!(v.select0(zeros), None);
+    }
+
+    #[test]
+    fn insert_set_remove() {
+        let mut v = DynamicBitVector::new();
+        let mut expected = Vec::new();
+        check(&v, &expected);
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        for _ in 0..40000 {
+            x ^= x << 13; x ^= x >> 7; x ^= x << 17;
+            let index = (x >> 16) as usize % (expected.len() + 1);
+            v.insert(index, x & 1 == 1);
+            expected.insert(index, x & 1 == 1);
+        }
+        check(&v, &expected);
+        for i in (0..expected.len()).step_by(3) {
+            assert_eq!(v.set(i, i % 2 == 0), expected[i]);
+            expected[i] = i % 2 == 0;
+        }
+        check(&v, &expected);
+        for _ in 0..39000 {
+            x ^= x << 13; x ^= x >> 7; x ^= x << 17;
+            let index = (x >> 16) as usize % expected.len();
+            assert_eq!(v.remove(index), expected.remove(index));
+        }
+        check(&v, &expected);
+        while !expected.is_empty() {
+            assert_eq!(v.remove(0), expected.remove(0));
+        }
+        check(&v, &expected);
+    }
+
+    #[test]
+    fn freeze() {
+        let v: DynamicBitVector = (0..5000).map(|i| i % 7 == 0).collect();
+        let (a, ones) = v.freeze::<ArrayWithRank101111>();
+        assert_eq!(ones, v.count_ones() as u64);
+        for i in 0..5000 {
+            assert_eq!(a.rank(i), v.rank(i));
+        }
+    }
+}
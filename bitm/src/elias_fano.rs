@@ -0,0 +1,206 @@
+use std::iter::FusedIterator;
+use dyn_size_of::{GetSize, SizeReport};
+use super::{BitAccess, BitVec, BitOnesIterator, BitArrayWithRank, ArrayWithRank101111};
+use super::select::last_satisfying;
+
+/// Elias-Fano representation of a non-decreasing sequence of `u64` values.
+///
+/// Each value is split into `lo_bits` lowest bits, stored as-is in the `lo` array,
+/// and the remaining high part, stored in unary (as gaps between successive ones) in the `hi` bit array.
+/// It uses about *2+log2(u/n)* bits per value, where *u* is the largest value and *n* is the number of values.
+/// It supports `get` in the time of `select1` of [`ArrayWithRank101111`] (*O(log n)* in the worst case),
+/// and `successor` and `predecessor` queries, which use `select0` and binary search the values sharing
+/// the high part with the query, in *O(log n)* time (and in *O(1)* expected time, assuming roughly uniform distribution of values).
+///
+/// See:
+/// - P. Elias, *Efficient storage and retrieval by content and address of static files*, J. ACM 21(2) (1974), 246–260.
+/// - R. M. Fano, *On the number of bits required to implement an associative memory*,
+///   Memorandum 61, Computer Structures Group, Project MAC, MIT, Cambridge, Mass., 1971.
+#[derive(Clone)]
+pub struct EliasFano {
+    hi: ArrayWithRank101111,  // high parts: the i-th one is at index i + (i-th value >> lo_bits)
+    lo: Box<[u64]>,   // low parts: lo_bits lowest bits of each value
+    lo_bits: u8,
+    len: usize
+}
+
+impl GetSize for EliasFano {
+    fn size_bytes_dyn(&self) -> usize { self.hi.size_bytes_dyn() + self.lo.size_bytes_dyn() }
+    const USES_DYN_MEM: bool = true;
+    fn size_report(&self) -> SizeReport { SizeReport::of(self).field("hi", &self.hi).field("lo", &self.lo) }
+}
+
+impl EliasFano {
+    /// Constructs `EliasFano` that stores given non-decreasing sequence of `values`.
+    pub fn new(values: &[u64]) -> Self {
+        debug_assert!(values.windows(2).all(|w| w[0] <= w[1]), "EliasFano requires a non-decreasing sequence");
+        let len = values.len();
+        let max = values.last().copied().unwrap_or(0);
+        let lo_bits = if len == 0 { 0 } else { (max / len as u64).checked_ilog2().map_or(0, |b| b as u8) };
+        let mut lo = Box::<[u64]>::with_zeroed_bits(len * lo_bits as usize);
+        let mut hi = Box::<[u64]>::with_zeroed_bits(len + (max >> lo_bits) as usize + 1);
+        for (i, v) in values.iter().enumerate() {
+            if lo_bits != 0 { lo.init_fragment(i, v & super::n_lowest_bits(lo_bits), lo_bits); }
+            hi.set_bit(i + (v >> lo_bits) as usize);
+        }
+        Self { hi: ArrayWithRank101111::build(hi).0, lo, lo_bits, len }
+    }
+
+    /// Returns the number of stored values.
+    #[inline] pub fn len(&self) -> usize { self.len }
+
+    /// Returns whether `self` stores no values.
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns the lowest part of the value with given `index`.
+    #[inline] fn lo(&self, index: usize) -> u64 {
+        if self.lo_bits == 0 { 0 } else { self.lo.get_fragment(index, self.lo_bits) }
+    }
+
+    /// Returns the value at given `index`, which must be less than `self.len()`.
+    #[inline] fn value(&self, index: usize, hi_position: usize) -> u64 {
+        (((hi_position - index) as u64) << self.lo_bits) | self.lo(index)
+    }
+
+    /// Returns the value with given `index` or `None` if `index` is not less than `self.len()`.
+    pub fn get(&self, index: usize) -> Option<u64> {
+        (index < self.len).then(|| self.value(index, self.hi.select1(index as u64).unwrap()))
+    }
+
+    /// Returns the index of the first value which is not less than `x`, or `self.len()` if there is no such value.
+    pub(crate) fn lower_bound(&self, x: u64) -> usize {
+        let x_hi = x >> self.lo_bits;
+        let bucket_begin = if x_hi == 0 { 0 } else {
+            match self.hi.select0(x_hi - 1) { Some(p) => p + 1, None => return self.len }
+        };
+        // the values in the bucket share high part x_hi, so binary search their low parts
+        let begin = bucket_begin - x_hi as usize;
+        let end = self.hi.select0(x_hi).map_or(self.len, |p| p - x_hi as usize);
+        let x_lo = x & super::n_lowest_bits(self.lo_bits);
+        last_satisfying(begin, end + 1, |i| i == begin || self.lo(i - 1) < x_lo)
+    }
+
+    /// Returns the index and the value of the smallest stored value which is not less than `x`,
+    /// or `None` if all stored values are less than `x`.
+    pub fn successor(&self, x: u64) -> Option<(usize, u64)> {
+        let index = self.lower_bound(x);
+        (index < self.len).then(|| (index, self.get(index).unwrap()))
+    }
+
+    /// Returns the index and the value of the largest stored value which is not greater than `x`,
+    /// or `None` if all stored values are greater than `x`.
+    pub fn predecessor(&self, x: u64) -> Option<(usize, u64)> {
+        let end = if x == u64::MAX { self.len } else { self.lower_bound(x + 1) };
+        let index = end.checked_sub(1)?;
+        Some((index, self.get(index).unwrap()))
+    }
+
+    /// Returns iterator over stored values.
+    #[inline] pub fn iter(&self) -> EliasFanoIterator<'_> {
+        EliasFanoIterator { ef: self, hi_ones: self.hi.content.bit_ones(), index: 0 }
+    }
+}
+
+impl From<&[u64]> for EliasFano {
+    #[inline] fn from(values: &[u64]) -> Self { Self::new(values) }
+}
+
+impl<'a> IntoIterator for &'a EliasFano {
+    type Item = u64;
+    type IntoIter = EliasFanoIterator<'a>;
+    #[inline] fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+/// Iterator over values stored in [`EliasFano`].
+pub struct EliasFanoIterator<'a> {
+    ef: &'a EliasFano,
+    hi_ones: BitOnesIterator<'a>,
+    index: usize
+}
+
+impl<'a> Iterator for EliasFanoIterator<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let position = self.hi_ones.next()?;
+        let result = self.ef.value(self.index, position);
+        self.index += 1;
+        Some(result)
+    }
+
+    #[inline] fn size_hint(&self) -> (usize, Option<usize>) {
+        let result = self.len();
+        (result, Some(result))
+    }
+}
+
+impl<'a> ExactSizeIterator for EliasFanoIterator<'a> {
+    #[inline] fn len(&self) -> usize { self.ef.len - self.index }
+}
+
+impl<'a> FusedIterator for EliasFanoIterator<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_ef(values: &[u64]) {
+        let ef = EliasFano::new(values);
+        assert_eq!(ef.len(), values.len());
+        assert_eq!(ef.iter().collect::<Vec<_>>(), values);
+        for (i, v) in values.iter().enumerate() {
+            assert_eq!(ef.get(i), Some(*v));
+        }
+        assert_eq!(ef.get(values.len()), None);
+        let max = values.last().copied().unwrap_or(0);
+        for x in 0..=max+2 {
+            let succ = values.iter().position(|v| *v >= x);
+            assert_eq!(ef.successor(x), succ.map(|i| (i, values[i])), "successor({})", x);
+            let pred = values.iter().rposition(|v| *v <= x);
+            assert_eq!(ef.predecessor(x), pred.map(|i| (i, values[i])), "predecessor({})", x);
+        }
+    }
+
+    #[test]
+    fn empty() {
+        test_ef(&[]);
+    }
+
+    #[test]
+    fn small() {
+        test_ef(&[0]);
+        test_ef(&[5]);
+        test_ef(&[0, 0, 0]);
+        test_ef(&[1, 2, 3, 4, 5]);
+        test_ef(&[3, 7, 7, 8, 20, 100, 101, 1000]);
+    }
+
+    #[test]
+    fn big() {
+        test_ef(&(0..3000).map(|i| i * 7 + i % 5).collect::<Vec<_>>());
+        test_ef(&(0..3000).map(|i| i / 3).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn clustered() {    // long buckets of values with the same high part
+        let mut values: Vec<u64> = (0..2000).map(|i| 50000 + i * 3).collect();
+        values.extend((0..2000).map(|i| 1 << 30 | i));
+        let ef = EliasFano::new(&values);
+        for (i, v) in values.iter().enumerate() {
+            assert_eq!(ef.successor(*v), Some((i, *v)));
+            assert_eq!(ef.predecessor(*v), Some((i, *v)));
+        }
+        assert_eq!(ef.successor(50001), Some((1, 50003)));
+        assert_eq!(ef.successor(60000), Some((2000, 1 << 30)));
+        assert_eq!(ef.predecessor(1 << 29), Some((1999, 50000 + 1999 * 3)));
+    }
+
+    #[test]
+    fn large_values() {
+        let ef = EliasFano::new(&[0, 1 << 40, u64::MAX - 1, u64::MAX]);
+        assert_eq!(ef.iter().collect::<Vec<_>>(), [0, 1 << 40, u64::MAX - 1, u64::MAX]);
+        assert_eq!(ef.successor(1), Some((1, 1 << 40)));
+        assert_eq!(ef.predecessor(u64::MAX - 2), Some((1, 1 << 40)));
+        assert_eq!(ef.predecessor(u64::MAX), Some((3, u64::MAX)));
+    }
+}
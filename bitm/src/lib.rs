@@ -1,11 +1,44 @@
 #![doc = include_str!("../README.md")]
 
 mod array_with_rank;
-pub use array_with_rank::{ArrayWithRank101111, ArrayWithRankSimple, BitArrayWithRank};
+pub use array_with_rank::{ArrayWithRank101111, ArrayWithRankSimple, ArrayWithRankSimple64, RankCounter, Rank, BitArrayWithRank};
+
+mod interleaved_rank;
+pub use interleaved_rank::ArrayWithRankInterleaved;
+
+mod select;
+pub use select::{BitArrayWithRankSelect, select64};
 
 mod bitvec;
 pub use bitvec::*;
 
+mod bit_vector;
+pub use bit_vector::{BitVector, BitVectorIterator, BitZerosIterator};
+
+mod packed_ints;
+pub use packed_ints::{PackedInts, PackedIntsIterator, bits_to_store};
+
+mod rrr;
+pub use rrr::ArrayWithRankRRR;
+
+mod wavelet_matrix;
+pub use wavelet_matrix::WaveletMatrix;
+
+mod elias_fano;
+pub use elias_fano::{EliasFano, EliasFanoIterator};
+
+mod sparse;
+pub use sparse::{ArrayWithRankSparse, SparseOnesIterator};
+
+mod dynamic;
+pub use dynamic::DynamicBitVector;
+
+mod balanced_parens;
+pub use balanced_parens::BalancedParens;
+
+mod louds;
+pub use louds::Louds;
+
 /// Returns ceil of `n/d`.
 #[inline(always)] pub const fn ceiling_div(n: usize, d: usize) -> usize { (n+d-1)/d }
 
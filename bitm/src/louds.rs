@@ -0,0 +1,164 @@
+use dyn_size_of::{GetSize, SizeReport};
+use super::{BitVec, BitArrayWithRank, ArrayWithRank101111, BitVector};
+
+/// Level-order unary degree sequence (LOUDS) representation of an ordinal tree.
+///
+/// The nodes are identified by their indices in level (breadth-first) order, the root has index 0.
+/// The tree is stored as the bits `10` followed by the degree of each node written in unary
+/// (`degree` ones followed by a zero), which takes *2n+1* bits for *n* nodes,
+/// plus the space of [`ArrayWithRank101111`] rank and select structure.
+/// Each navigational query takes a constant number of rank and select queries
+/// (see [`ArrayWithRank101111`] for their complexity).
+///
+/// See:
+/// - G. Jacobson, *Space-efficient static trees and graphs*,
+///   30th Annual Symposium on Foundations of Computer Science (FOCS), 1989, 549–554.
+///   <https://doi.org/10.1109/SFCS.1989.63533>
+#[derive(Clone)]
+pub struct Louds {
+    bits: ArrayWithRank101111,  // the k-th one represents the node with index k
+    len: usize  // number of nodes
+}
+
+impl GetSize for Louds {
+    #[inline] fn size_bytes_dyn(&self) -> usize { self.bits.size_bytes_dyn() }
+    const USES_DYN_MEM: bool = true;
+    fn size_report(&self) -> SizeReport { SizeReport::of(self).field("bits", &self.bits) }
+}
+
+impl Louds {
+    /// Constructs the tree in which the nodes have given `degrees` (numbers of children),
+    /// listed in level order, starting from the root.
+    /// Panics if the `degrees` do not describe a tree.
+    pub fn from_degrees<I: IntoIterator<Item = usize>>(degrees: I) -> Self {
+        let mut bits = BitVector::new();
+        bits.push(true);
+        bits.push(false);
+        let (mut len, mut nodes) = (0, 1);
+        for degree in degrees {
+            assert!(len < nodes, "Louds::from_degrees: degrees of more nodes than the tree includes");
+            for _ in 0..degree { bits.push(true); }
+            bits.push(false);
+            len += 1;
+            nodes += degree;
+        }
+        assert!(len == nodes || (len == 0 && nodes == 1), "Louds::from_degrees: degrees of too few nodes");
+        if len == 0 { bits.clear(); }
+        let mut content = Box::<[u64]>::with_zeroed_bits(bits.len() + 1);
+        content[..bits.words().len()].copy_from_slice(bits.words());
+        Self { bits: ArrayWithRank101111::build(content).0, len }
+    }
+
+    /// Returns the number of nodes in the tree.
+    #[inline] pub fn len(&self) -> usize { self.len }
+
+    /// Returns whether the tree is empty.
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns the position of the first bit of the unary degree of the `node`.
+    #[inline] fn degree_begin(&self, node: usize) -> usize {
+        self.bits.select0(node as u64).unwrap() + 1
+    }
+
+    /// Returns the position of the one that represents the `node`.
+    #[inline] fn node_position(&self, node: usize) -> usize {
+        self.bits.select1(node as u64).unwrap()
+    }
+
+    /// Returns the number of children of the `node`.
+    pub fn degree(&self, node: usize) -> usize {
+        assert!(node < self.len, "Louds::degree: node index out of bounds");
+        self.bits.select0(node as u64 + 1).unwrap() - self.degree_begin(node)
+    }
+
+    /// Returns whether the `node` has no children.
+    #[inline] pub fn is_leaf(&self, node: usize) -> bool {
+        assert!(node < self.len, "Louds::is_leaf: node index out of bounds");
+        !self.bits.get_bit(self.degree_begin(node))
+    }
+
+    /// Returns the `index`-th (counting from 0) child of the `node` or `None` if the `node` has no more than `index` children.
+    pub fn child(&self, node: usize, index: usize) -> Option<usize> {
+        if index >= self.degree(node) { return None; }
+        Some(self.bits.rank(self.degree_begin(node) + index) as usize)
+    }
+
+    /// Returns the first child of the `node` or `None` if the `node` is a leaf.
+    #[inline] pub fn first_child(&self, node: usize) -> Option<usize> {
+        if self.is_leaf(node) { None } else { Some(self.bits.rank(self.degree_begin(node)) as usize) }
+    }
+
+    /// Returns the next sibling of the `node` or `None` if the `node` is the last child of its parent (or the root).
+    pub fn next_sibling(&self, node: usize) -> Option<usize> {
+        assert!(node < self.len, "Louds::next_sibling: node index out of bounds");
+        self.bits.get_bit(self.node_position(node) + 1).then_some(node + 1)
+    }
+
+    /// Returns the parent of the `node` or `None` if the `node` is the root.
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        assert!(node < self.len, "Louds::parent: node index out of bounds");
+        if node == 0 { return None; }
+        let position = self.node_position(node);
+        Some(position - self.bits.rank(position) as usize - 1)
+    }
+
+    /// Returns the index of the `node` among the children of its parent (0 for the root).
+    pub fn child_rank(&self, node: usize) -> usize {
+        match self.parent(node) {
+            Some(parent) => self.node_position(node) - self.degree_begin(parent),
+            None => 0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_louds(degrees: &[usize]) {
+        let louds = Louds::from_degrees(degrees.iter().copied());
+        assert_eq!(louds.len(), degrees.len());
+        let mut parents = vec![None];
+        let mut children = Vec::new();
+        for (node, degree) in degrees.iter().enumerate() {
+            children.push((parents.len()..parents.len() + degree).collect::<Vec<_>>());
+            parents.resize(parents.len() + degree, Some(node));
+        }
+        for node in 0..degrees.len() {
+            assert_eq!(louds.degree(node), degrees[node]);
+            assert_eq!(louds.is_leaf(node), degrees[node] == 0);
+            assert_eq!(louds.parent(node), parents[node]);
+            assert_eq!(louds.first_child(node), children[node].first().copied());
+            for (i, child) in children[node].iter().enumerate() {
+                assert_eq!(louds.child(node, i), Some(*child));
+                assert_eq!(louds.child_rank(*child), i);
+                assert_eq!(louds.next_sibling(*child), children[node].get(i + 1).copied());
+            }
+            assert_eq!(louds.child(node, degrees[node]), None);
+        }
+        if !degrees.is_empty() {
+            assert_eq!(louds.next_sibling(0), None);
+            assert_eq!(louds.child_rank(0), 0);
+        }
+    }
+
+    #[test]
+    fn small() {
+        test_louds(&[]);
+        test_louds(&[0]);
+        test_louds(&[3, 2, 0, 1, 0, 0, 0]);
+        test_louds(&[1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn bigger() {
+        let mut degrees: Vec<usize> = Vec::new();
+        let mut nodes = 1;
+        while degrees.len() < nodes {
+            let degree = if nodes < 5000 { (degrees.len() * 7 + 3) % 5 } else { 0 };
+            degrees.push(degree);
+            nodes += degree;
+        }
+        test_louds(&degrees);
+    }
+}
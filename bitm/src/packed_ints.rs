@@ -0,0 +1,255 @@
+use std::io;
+use std::iter::FusedIterator;
+use binout::{AsIs, Serializer, VByte};
+use dyn_size_of::GetSize;
+use super::{BitAccess, BitVec};
+
+/// Returns the number of bits needed to store `value` (`0` for `value=0`).
+#[inline] pub fn bits_to_store(value: u64) -> u8 {
+    (u64::BITS - value.leading_zeros()) as u8
+}
+
+/// Vector of integers, each stored using the same number of bits (`bits_per_value`, up to 64).
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct PackedInts {
+    values: Box<[u64]>,
+    bits_per_value: u8,
+    len: usize
+}
+
+impl GetSize for PackedInts {
+    #[inline] fn size_bytes_dyn(&self) -> usize { self.values.size_bytes_dyn() }
+    const USES_DYN_MEM: bool = true;
+}
+
+impl PackedInts {
+    /// Constructs vector of `len` zeros, each stored using `bits_per_value` (up to 64) bits.
+    pub fn new(len: usize, bits_per_value: u8) -> Self {
+        assert!(bits_per_value <= 64, "PackedInts supports up to 64 bits per value");
+        Self { values: Box::with_zeroed_bits(len * bits_per_value as usize), bits_per_value, len }
+    }
+
+    /// Constructs vector of `len` `values`, each stored using `bits_per_value` (up to 64) bits.
+    /// Only `bits_per_value` lowest bits of each value are stored.
+    pub fn from_iter_bits<I: IntoIterator<Item = u64>>(values: I, len: usize, bits_per_value: u8) -> Self {
+        let mut result = Self::new(len, bits_per_value);
+        for (index, value) in values.into_iter().take(len).enumerate() {
+            result.set(index, value);
+        }
+        result
+    }
+
+    /// Constructs vector of `values`, each stored using the minimal number of bits needed to store the largest value.
+    pub fn from_slice(values: &[u64]) -> Self {
+        let bits_per_value = bits_to_store(values.iter().copied().max().unwrap_or(0));
+        Self::from_iter_bits(values.iter().copied(), values.len(), bits_per_value)
+    }
+
+    /// Returns the number of values stored.
+    #[inline] pub fn len(&self) -> usize { self.len }
+
+    /// Returns whether `self` stores no values.
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns the number of bits used to store each value.
+    #[inline] pub fn bits_per_value(&self) -> u8 { self.bits_per_value }
+
+    /// Returns the bit array that stores the values.
+    #[inline] pub fn content(&self) -> &[u64] { &self.values }
+
+    /// Returns the value with given `index` or `None` if `index` is out of bounds.
+    #[inline] pub fn get(&self, index: usize) -> Option<u64> {
+        (index < self.len).then(|| self.value(index))
+    }
+
+    /// Returns the value with given `index`, which must be less than `self.len()`.
+    #[inline] fn value(&self, index: usize) -> u64 {
+        match self.bits_per_value {
+            0 => 0,
+            64 => self.values[index],
+            bits => self.values.get_fragment(index, bits)
+        }
+    }
+
+    /// Sets the value with given `index` to `bits_per_value` lowest bits of `value`.
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: u64) {
+        assert!(index < self.len, "PackedInts::set: index {} is out of bounds (length is {})", index, self.len);
+        match self.bits_per_value {
+            0 => {},
+            64 => self.values[index] = value,
+            bits => self.values.set_fragment(index, value & super::n_lowest_bits(bits), bits)
+        }
+    }
+
+    /// Xors the value with given `index` with `bits_per_value` lowest bits of `value`.
+    /// Panics if `index` is out of bounds.
+    pub fn xor(&mut self, index: usize, value: u64) {
+        assert!(index < self.len, "PackedInts::xor: index {} is out of bounds (length is {})", index, self.len);
+        match self.bits_per_value {
+            0 => {},
+            64 => self.values[index] ^= value,
+            bits => self.values.xor_fragment(index, value & super::n_lowest_bits(bits), bits)
+        }
+    }
+
+    /// Returns iterator over stored values.
+    #[inline] pub fn iter(&self) -> PackedIntsIterator<'_> {
+        PackedIntsIterator { ints: self, index: 0, end: self.len }
+    }
+
+    /// Returns number of bytes which `write` will write.
+    pub fn write_bytes(&self) -> usize {
+        VByte::size(self.len) + AsIs::size(self.bits_per_value) + AsIs::array_content_size(&self.values)
+    }
+
+    /// Writes `self` to the `output`.
+    pub fn write(&self, output: &mut dyn io::Write) -> io::Result<()> {
+        VByte::write(output, self.len)?;
+        AsIs::write(output, self.bits_per_value)?;
+        AsIs::write_all(output, self.values.iter())
+    }
+
+    /// Reads `Self` from the `input`.
+    ///
+    /// Returns [`io::ErrorKind::InvalidData`] error if `bits_per_value` exceeds 64 or the total number of bits overflows.
+    pub fn read(input: &mut dyn io::Read) -> io::Result<Self> {
+        let len: usize = VByte::read(input)?;
+        let bits_per_value: u8 = AsIs::read(input)?;
+        if bits_per_value > 64 { return Err(io::ErrorKind::InvalidData.into()); }
+        let bits = len.checked_mul(bits_per_value as usize).ok_or(io::ErrorKind::InvalidData)?;
+        let values = AsIs::read_n(input, bits.div_ceil(64))?;
+        Ok(Self { values, bits_per_value, len })
+    }
+}
+
+impl FromIterator<u64> for PackedInts {
+    /// Constructs vector of values, each stored using the minimal number of bits needed to store the largest value.
+    fn from_iter<I: IntoIterator<Item = u64>>(iter: I) -> Self {
+        Self::from_slice(&iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl From<&[u64]> for PackedInts {
+    #[inline] fn from(values: &[u64]) -> Self { Self::from_slice(values) }
+}
+
+impl<'a> IntoIterator for &'a PackedInts {
+    type Item = u64;
+    type IntoIter = PackedIntsIterator<'a>;
+    #[inline] fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+/// Iterator over values stored in [`PackedInts`].
+pub struct PackedIntsIterator<'a> {
+    ints: &'a PackedInts,
+    index: usize,
+    end: usize
+}
+
+impl<'a> Iterator for PackedIntsIterator<'a> {
+    type Item = u64;
+
+    #[inline] fn next(&mut self) -> Option<Self::Item> {
+        (self.index < self.end).then(|| {
+            self.index += 1;
+            self.ints.value(self.index - 1)
+        })
+    }
+
+    #[inline] fn size_hint(&self) -> (usize, Option<usize>) {
+        let result = self.len();
+        (result, Some(result))
+    }
+}
+
+impl<'a> DoubleEndedIterator for PackedIntsIterator<'a> {
+    #[inline] fn next_back(&mut self) -> Option<Self::Item> {
+        (self.index < self.end).then(|| {
+            self.end -= 1;
+            self.ints.value(self.end)
+        })
+    }
+}
+
+impl<'a> ExactSizeIterator for PackedIntsIterator<'a> {
+    #[inline] fn len(&self) -> usize { self.end - self.index }
+}
+
+impl<'a> FusedIterator for PackedIntsIterator<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_set_xor() {
+        let mut p = PackedInts::new(10, 5);
+        assert_eq!(p.len(), 10);
+        assert_eq!(p.iter().collect::<Vec<_>>(), [0; 10]);
+        p.set(3, 0b10101);
+        p.set(9, 0b111111);    // too large value, only 5 bits are stored
+        assert_eq!(p.get(3), Some(0b10101));
+        assert_eq!(p.get(9), Some(0b11111));
+        assert_eq!(p.get(10), None);
+        p.xor(3, 0b00111);
+        assert_eq!(p.get(3), Some(0b10010));
+        assert_eq!(p.get(2), Some(0));
+        assert_eq!(p.get(4), Some(0));
+    }
+
+    #[test]
+    fn from_iter() {
+        let values = [3u64, 0, 1000, 7, 999, 1];
+        let p: PackedInts = values.iter().copied().collect();
+        assert_eq!(p.bits_per_value(), 10);
+        assert_eq!(p.iter().collect::<Vec<_>>(), values);
+        assert_eq!(p.iter().rev().collect::<Vec<_>>(), values.iter().rev().copied().collect::<Vec<_>>());
+        let zeros = PackedInts::from_slice(&[0, 0, 0]);
+        assert_eq!(zeros.bits_per_value(), 0);
+        assert_eq!(zeros.iter().collect::<Vec<_>>(), [0, 0, 0]);
+    }
+
+    #[test]
+    fn full_width() {
+        let values = [u64::MAX, 0, 1 << 63, 12345];
+        let mut p: PackedInts = values.iter().copied().collect();
+        assert_eq!(p.bits_per_value(), 64);
+        assert_eq!(p.iter().collect::<Vec<_>>(), values);
+        p.xor(0, 1);
+        p.set(1, u64::MAX - 1);
+        assert_eq!(p.get(0), Some(u64::MAX - 1));
+        assert_eq!(p.get(1), Some(u64::MAX - 1));
+        let mut buff = Vec::new();
+        p.write(&mut buff).unwrap();
+        assert_eq!(PackedInts::read(&mut &buff[..]).unwrap(), p);
+    }
+
+    #[test]
+    fn read_write() {
+        let p = PackedInts::from_iter_bits((0..100).map(|v| v * 3), 100, 9);
+        let mut buff = Vec::new();
+        p.write(&mut buff).unwrap();
+        assert_eq!(buff.len(), p.write_bytes());
+        let read = PackedInts::read(&mut &buff[..]).unwrap();
+        assert_eq!(p, read);
+        assert_eq!(PackedInts::read(&mut &buff[..buff.len()-1]).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn read_overflowing_len() {
+        let mut buff = Vec::new();
+        VByte::write(&mut buff, usize::MAX).unwrap();
+        AsIs::write(&mut buff, 64u8).unwrap();
+        assert_eq!(PackedInts::read(&mut &buff[..]).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_bits_to_store() {
+        assert_eq!(bits_to_store(0), 0);
+        assert_eq!(bits_to_store(1), 1);
+        assert_eq!(bits_to_store(255), 8);
+        assert_eq!(bits_to_store(256), 9);
+        assert_eq!(bits_to_store(u64::MAX), 64);
+    }
+}
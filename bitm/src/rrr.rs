@@ -0,0 +1,292 @@
+use dyn_size_of::{GetSize, SizeReport};
+use super::{BitAccess, BitVec, Rank, BitArrayWithRank, BitArrayWithRankSelect, select64, n_lowest_bits};
+use super::select::last_satisfying;
+
+/// Returns table of binomial coefficients: `BINOMIAL[n][k]` = *n choose k*, for *n, k ≤ 64*.
+const fn binomial_table() -> [[u64; 65]; 65] {
+    let mut result = [[0u64; 65]; 65];
+    let mut n = 0;
+    while n <= 64 {
+        result[n][0] = 1;
+        let mut k = 1;
+        while k <= n {
+            result[n][k] = result[n-1][k-1] + if k < n { result[n-1][k] } else { 0 };
+            k += 1;
+        }
+        n += 1;
+    }
+    result
+}
+
+/// `BINOMIAL[n][k]` = *n choose k*, for *n, k ≤ 64*.
+static BINOMIAL: [[u64; 65]; 65] = binomial_table();
+
+/// Returns the number of bits needed to store offset of 64-bit word with `class` ones.
+const fn offset_bits_table() -> [u8; 65] {
+    let binomial = binomial_table();
+    let mut result = [0u8; 65];
+    let mut k = 0;
+    while k <= 64 {
+        result[k] = (u64::BITS - (binomial[64][k] - 1).leading_zeros()) as u8;
+        k += 1;
+    }
+    result
+}
+
+/// `OFFSET_BITS[class]` = the number of bits needed to store offset of 64-bit word with `class` ones.
+const OFFSET_BITS: [u8; 65] = offset_bits_table();
+
+/// Number of bits used to store the class (the number of ones) of each 64-bit block.
+const CLASS_BITS: u8 = 7;
+
+/// Number of 64-bit blocks in a superblock, for which rank and offset position are sampled.
+const SUPERBLOCK_BLOCKS: usize = 32;
+
+/// Returns the offset of `word` among all 64-bit words with the same number of ones,
+/// according to combinatorial number system.
+fn encode(mut word: u64) -> u64 {
+    let mut result = 0;
+    let mut i = 1;
+    while word != 0 {
+        result += BINOMIAL[word.trailing_zeros() as usize][i];
+        word &= word - 1;
+        i += 1;
+    }
+    result
+}
+
+/// Returns the 64-bit word with `class` ones, whose offset is `offset` (inverse of [`encode`]).
+fn decode(class: u8, mut offset: u64) -> u64 {
+    let mut result = 0;
+    let mut c = 64;
+    for i in (1..=class as usize).rev() {
+        loop {
+            c -= 1;
+            if BINOMIAL[c][i] <= offset { break; }
+        }
+        result |= 1 << c;
+        offset -= BINOMIAL[c][i];
+    }
+    result
+}
+
+/// The structure that holds compressed array of bits and its rank structure.
+/// It can returns the number of ones in first `index` bits of the array (see `rank` method) in *O(1)* time.
+///
+/// It uses a variant of the RRR (class/offset) representation, in which the array is divided into 64-bit blocks
+/// and each block is stored as its class (the number of ones, using 7 bits)
+/// and its offset (the index of the block among all blocks of the same class, using *log2(64 choose class)* bits).
+/// Additionally, for every 32 blocks, the rank and the position of the first offset are sampled.
+/// Thus, the structure takes less space than the uncompressed array if it is sparse, dense, or clustered.
+///
+/// See:
+/// - R. Raman, V. Raman, S. S. Rao, *Succinct indexable dictionaries with applications to encoding k-ary trees and multisets*,
+///   Proceedings of the 13th Annual ACM-SIAM Symposium on Discrete Algorithms (SODA), 2002, 233–242.
+/// - G. Navarro, E. Providel, *Fast, Small, Simple Rank/Select on Bitmaps*,
+///   In: Klasing R. (eds) Experimental Algorithms. SEA 2012. Lecture Notes in Computer Science, vol 7276. Springer.
+///   <https://doi.org/10.1007/978-3-642-30850-5_26>
+#[derive(Clone)]
+pub struct ArrayWithRankRRR {
+    classes: Box<[u64]>,    // 7-bit classes of subsequent 64-bit blocks
+    offsets: Box<[u64]>,    // concatenated, variable-length offsets of subsequent 64-bit blocks
+    ranks: Box<[u64]>,      // the number of ones before each superblock (and the total number of ones at the end)
+    positions: Box<[u64]>,  // the position (in offsets) of the first offset of each superblock (and the length of offsets at the end)
+    len: usize  // number of 64-bit blocks
+}
+
+impl GetSize for ArrayWithRankRRR {
+    fn size_bytes_dyn(&self) -> usize {
+        self.classes.size_bytes_dyn() + self.offsets.size_bytes_dyn() + self.ranks.size_bytes_dyn() + self.positions.size_bytes_dyn()
+    }
+    const USES_DYN_MEM: bool = true;
+    fn size_report(&self) -> SizeReport {
+        SizeReport::of(self).field("classes", &self.classes).field("offsets", &self.offsets)
+            .field("ranks", &self.ranks).field("positions", &self.positions)
+    }
+}
+
+impl ArrayWithRankRRR {
+    /// Returns the class (the number of ones) of the block with given index.
+    #[inline] fn class(&self, block: usize) -> u8 {
+        self.classes.get_fragment(block, CLASS_BITS) as u8
+    }
+
+    /// Returns the content of the block with given index whose offset begins at given `position`.
+    #[inline] fn decode_block(&self, block: usize, position: usize) -> u64 {
+        let class = self.class(block);
+        let bits = OFFSET_BITS[class as usize];
+        decode(class, if bits == 0 { 0 } else { self.offsets.get_bits(position, bits) })
+    }
+
+    /// Returns the number of ones before and the position of the offset of the block with given index.
+    #[inline] fn block_rank_position(&self, block: usize) -> (u64, usize) {
+        let superblock = block / SUPERBLOCK_BLOCKS;
+        let mut rank = self.ranks[superblock];
+        let mut position = self.positions[superblock] as usize;
+        for b in superblock * SUPERBLOCK_BLOCKS..block {
+            let class = self.class(b);
+            rank += class as u64;
+            position += OFFSET_BITS[class as usize] as usize;
+        }
+        (rank, position)
+    }
+
+    /// Returns the number of bits in the array.
+    #[inline] pub fn len(&self) -> usize { self.len * 64 }
+
+    /// Returns whether the array is empty.
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns the 64-bit word with given `index` of the (uncompressed) array.
+    pub fn word(&self, index: usize) -> u64 {
+        let (_, position) = self.block_rank_position(index);
+        self.decode_block(index, position)
+    }
+
+    /// Returns the bit with given `index`.
+    #[inline] pub fn get_bit(&self, index: usize) -> bool {
+        self.word(index / 64) & (1 << (index % 64)) != 0
+    }
+
+    /// Returns the uncompressed array.
+    pub fn decompress(&self) -> Box<[u64]> {
+        let mut position = 0;
+        (0..self.len).map(|block| {
+            let word = self.decode_block(block, position);
+            position += OFFSET_BITS[self.class(block) as usize] as usize;
+            word
+        }).collect()
+    }
+
+    /// Returns the position of the `rank`-th one (if `ONE` is `true`) or zero (otherwise).
+    fn select<const ONE: bool>(&self, rank: u64) -> Option<usize> {
+        let before = |superblock: usize| {
+            let ones = self.ranks[superblock];
+            if ONE { ones } else { (superblock * SUPERBLOCK_BLOCKS * 64) as u64 - ones }
+        };
+        let superblock = last_satisfying(0, self.ranks.len(), |s| before(s) <= rank);
+        let mut rank = rank - before(superblock);
+        let mut position = self.positions[superblock] as usize;
+        for block in superblock * SUPERBLOCK_BLOCKS..self.len.min((superblock+1) * SUPERBLOCK_BLOCKS) {
+            let class = self.class(block);
+            let count = if ONE { class as u64 } else { 64 - class as u64 };
+            if rank < count {
+                let word = self.decode_block(block, position);
+                return Some(block * 64 + select64(if ONE { word } else { !word }, rank as u8) as usize);
+            }
+            rank -= count;
+            position += OFFSET_BITS[class as usize] as usize;
+        }
+        None
+    }
+}
+
+impl BitArrayWithRank for ArrayWithRankRRR {
+    fn build(content: Box<[u64]>) -> (Self, u64) {
+        let len = content.len();
+        let mut classes = Box::<[u64]>::with_zeroed_bits(len * CLASS_BITS as usize);
+        let offsets_len = content.iter().map(|w| OFFSET_BITS[w.count_ones() as usize] as usize).sum();
+        let mut offsets = Box::<[u64]>::with_zeroed_bits(offsets_len);
+        let mut ranks = Vec::with_capacity(super::ceiling_div(len, SUPERBLOCK_BLOCKS) + 1);
+        let mut positions = Vec::with_capacity(super::ceiling_div(len, SUPERBLOCK_BLOCKS) + 1);
+        let mut rank = 0;
+        let mut position = 0;
+        for (block, word) in content.iter().enumerate() {
+            if block % SUPERBLOCK_BLOCKS == 0 {
+                ranks.push(rank);
+                positions.push(position as u64);
+            }
+            let class = word.count_ones() as u8;
+            classes.init_fragment(block, class as u64, CLASS_BITS);
+            let bits = OFFSET_BITS[class as usize];
+            if bits != 0 { offsets.set_bits(position, encode(*word), bits); }
+            rank += class as u64;
+            position += bits as usize;
+        }
+        ranks.push(rank);
+        positions.push(position as u64);
+        (Self { classes, offsets, ranks: ranks.into_boxed_slice(), positions: positions.into_boxed_slice(), len }, rank)
+    }
+}
+
+impl Rank for ArrayWithRankRRR {
+    fn rank(&self, index: usize) -> u64 {
+        let block = index / 64;
+        let (rank, position) = self.block_rank_position(block);
+        if block == self.len { return rank; }  // index points to the end of the array
+        rank + (self.decode_block(block, position) & n_lowest_bits(index as u8 % 64)).count_ones() as u64
+    }
+
+    #[inline] fn get_bit(&self, index: usize) -> bool {
+        Self::get_bit(self, index)
+    }
+}
+
+impl BitArrayWithRankSelect for ArrayWithRankRRR {
+    #[inline] fn select1(&self, rank: u64) -> Option<usize> {
+        self.select::<true>(rank)
+    }
+
+    #[inline] fn select0(&self, rank: u64) -> Option<usize> {
+        self.select::<false>(rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bits_to_store;
+
+    #[test]
+    fn encode_decode() {
+        for word in [0, 1, 2, 3, 1<<63, u64::MAX, u64::MAX-1, 0xF0F0_1234_0000_ABCD, 0x8000_0000_0000_0001] {
+            let class = word.count_ones() as u8;
+            let offset = encode(word);
+            assert!(offset < BINOMIAL[64][class as usize]);
+            assert!(bits_to_store(offset) <= OFFSET_BITS[class as usize]);
+            assert_eq!(decode(class, offset), word);
+        }
+        assert_eq!(OFFSET_BITS[0], 0);
+        assert_eq!(OFFSET_BITS[64], 0);
+        assert_eq!(OFFSET_BITS[1], 6);
+    }
+
+    #[test]
+    fn rank_select() {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;  // xorshift generator of pseudo-random content
+        let content: Box<[u64]> = (0..1000).map(|i| {
+            x ^= x << 13; x ^= x >> 7; x ^= x << 17;
+            match i % 100 { 0..=29 => 0, 30..=39 => u64::MAX, 40..=59 => x & (x >> 5) & (x >> 11), _ => x }
+        }).collect();
+        let (a, ones) = ArrayWithRankRRR::build(content.clone());
+        assert_eq!(ones, content.count_bit_ones() as u64);
+        assert_eq!(a.decompress(), content);
+        assert!(a.size_bytes_dyn() < content.size_bytes_dyn());
+        let mut rank = 0;
+        let mut zeros = 0;
+        for i in 0..content.len() * 64 {
+            assert_eq!(a.rank(i), rank, "rank({})", i);
+            assert_eq!(a.get_bit(i), content.get_bit(i));
+            if content.get_bit(i) {
+                assert_eq!(a.select1(rank), Some(i), "select1({})", rank);
+                rank += 1;
+            } else {
+                assert_eq!(a.select0(zeros), Some(i), "select0({})", zeros);
+                zeros += 1;
+            }
+        }
+        assert_eq!(a.rank(content.len() * 64), ones);
+        assert_eq!(a.select1(ones), None);
+        assert_eq!(a.select0(zeros), None);
+    }
+
+    #[test]
+    fn empty() {
+        let (a, ones) = ArrayWithRankRRR::build(Box::new([]));
+        assert_eq!(ones, 0);
+        assert!(a.is_empty());
+        assert_eq!(a.rank(0), 0);
+        assert_eq!(a.select1(0), None);
+        assert_eq!(a.select0(0), None);
+    }
+}
@@ -0,0 +1,134 @@
+use super::Rank;
+use super::bitvec::{next_bit, prev_bit};
+
+/// Number of ones (or zeros) between successive select hints.
+/// Each hint points to the block that contains the one (zero) with the rank that is a multiple of this value.
+pub(crate) const SELECT_SAMPLING: u64 = 8192;
+
+/// The trait implemented by the types which, beside the rank structure, hold sampled select hints,
+/// and thanks to them can quickly find the position of a one or a zero of given rank (see `select1` and `select0` methods).
+///
+/// The hints only narrow the search to a range of blocks, which is then binary searched,
+/// so select is not constant-time in the worst case (see the documentation of the implementors for details).
+pub trait BitArrayWithRankSelect: Rank {
+    /// Returns the position of the `rank`-th one (counting from 0) in the `content`
+    /// or `None` if there are no more than `rank` ones in the `content`.
+    fn select1(&self, rank: u64) -> Option<usize>;
+
+    /// Returns the position of the `rank`-th zero (counting from 0) in the `content`
+    /// or `None` if there are no more than `rank` zeros in the `content`.
+    fn select0(&self, rank: u64) -> Option<usize>;
+}
+
+/// Returns the index of the `rank`-th (counting from 0) bit set in `word`.
+/// The result is unspecified if `word` has no more than `rank` ones.
+#[inline] pub fn select64(mut word: u64, mut rank: u8) -> u8 {
+    let mut shift = 0;
+    loop {  // skip whole bytes
+        let ones = (word as u8).count_ones() as u8;
+        if rank < ones || shift == 56 { break; }
+        rank -= ones;
+        word >>= 8;
+        shift += 8;
+    }
+    for _ in 0..rank { word &= word.wrapping_sub(1); }
+    shift + word.trailing_zeros() as u8
+}
+
+/// Returns the index of the first one (if `ONE` is `true`) or zero (otherwise) in `content` which is not less than `from`.
+/// Searches the 512-bit block containing `from` directly and then, using `rank` and `select`
+/// (`select1` if `ONE` is `true`, `select0` otherwise), skips the following blocks that do not contain the bit sought.
+pub(crate) fn next_with_rank<const ONE: bool, R, S>(content: &[u64], from: usize, rank: R, select: S) -> Option<usize>
+    where R: FnOnce(usize) -> u64, S: FnOnce(u64) -> Option<usize>
+{
+    let block_end = (from / 512 + 1) * 8;   // index of the first word of the next block
+    if let Some(result) = next_bit::<ONE>(content, from, block_end.min(content.len())) { return Some(result); }
+    if block_end >= content.len() { return None; }
+    let ones = rank(block_end * 64);
+    select(if ONE { ones } else { (block_end * 64) as u64 - ones })
+}
+
+/// Returns the index of the last one (if `ONE` is `true`) or zero (otherwise) in `content` which is not greater than `from`.
+/// Searches the 512-bit block containing `from` directly and then, using `rank` and `select`
+/// (`select1` if `ONE` is `true`, `select0` otherwise), skips the preceding blocks that do not contain the bit sought.
+pub(crate) fn prev_with_rank<const ONE: bool, R, S>(content: &[u64], from: usize, rank: R, select: S) -> Option<usize>
+    where R: FnOnce(usize) -> u64, S: FnOnce(u64) -> Option<usize>
+{
+    let from = from.min((content.len() * 64).checked_sub(1)?);
+    let block_begin = from / 512 * 8;   // index of the first word of the block
+    if let Some(result) = prev_bit::<ONE>(content, from, block_begin) { return Some(result); }
+    let ones = rank(block_begin * 64);
+    select((if ONE { ones } else { (block_begin * 64) as u64 - ones }).checked_sub(1)?)
+}
+
+/// Returns the largest `i` in [`begin`, `end`) for which `pred(i)` is `true`.
+/// `pred(begin)` must be `true` and `pred` must be monotone (`true` for a prefix of the range).
+#[inline] pub(crate) fn last_satisfying<P: Fn(usize) -> bool>(mut begin: usize, mut end: usize, pred: P) -> usize {
+    while end - begin > 1 {
+        let mid = (begin + end) / 2;
+        if pred(mid) { begin = mid } else { end = mid }
+    }
+    begin
+}
+
+/// Returns the range of blocks that can contain the item of the given `rank`, according to the sampled `hints`.
+/// Returns `None` if there are not enough items to consider.
+#[inline] pub(crate) fn hinted_blocks<H: Copy + Into<u64>>(hints: &[H], rank: u64, blocks_len: usize) -> Option<(usize, usize)> {
+    let hint = (rank / SELECT_SAMPLING) as usize;
+    let begin = (*hints.get(hint)?).into() as usize;
+    let end = hints.get(hint+1).map_or(blocks_len, |e| (*e).into() as usize + 1);
+    Some((begin, end))
+}
+
+/// Returns the position of the `rank`-th one (if `ONE` is `true`) or zero (otherwise)
+/// in the (at most 8) words of `content` that begin with `first_word`,
+/// or `None` if these words contain no more than `rank` ones (zeros).
+#[inline] pub(crate) fn select_in_block<const ONE: bool>(content: &[u64], first_word: usize, mut rank: u64) -> Option<usize> {
+    for (i, word) in content[first_word..].iter().take(8).enumerate() {
+        let word = if ONE { *word } else { !*word };
+        let ones = word.count_ones() as u64;
+        if rank < ones { return Some((first_word + i) * 64 + select64(word, rank as u8) as usize); }
+        rank -= ones;
+    }
+    None
+}
+
+/// Pushes to `hints` the index of the block (`block_index`) for each
+/// multiple of [`SELECT_SAMPLING`] in range [`*next_hinted_rank`, `rank_after_block`),
+/// and updates `next_hinted_rank` accordingly.
+/// Returns an error if a hint should be pushed, but `block_index` cannot be represented by `H`.
+#[inline] pub(crate) fn push_hints<H: TryFrom<u64>>(hints: &mut Vec<H>, next_hinted_rank: &mut u64, rank_after_block: u64, block_index: usize) -> Result<(), H::Error> {
+    while *next_hinted_rank < rank_after_block {
+        hints.push(H::try_from(block_index as u64)?);
+        *next_hinted_rank += SELECT_SAMPLING;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select64() {
+        assert_eq!(select64(1, 0), 0);
+        assert_eq!(select64(0b1010, 0), 1);
+        assert_eq!(select64(0b1010, 1), 3);
+        assert_eq!(select64(u64::MAX, 63), 63);
+        assert_eq!(select64(1<<63, 0), 63);
+        assert_eq!(select64(0xFF00_0000_0000_0100, 1), 56);
+        assert_eq!(select64(0xFF00_0000_0000_0100, 8), 63);
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn test_push_hints() {
+        let (mut hints, mut next) = (Vec::<u32>::new(), 0);
+        assert!(push_hints(&mut hints, &mut next, 2 * SELECT_SAMPLING + 1, 5).is_ok());
+        assert_eq!(hints, [5, 5, 5]);
+        assert!(push_hints(&mut hints, &mut next, 3 * SELECT_SAMPLING + 1, 1 << 32).is_err());
+        let (mut hints, mut next) = (Vec::<u64>::new(), 0);
+        assert!(push_hints(&mut hints, &mut next, 1, 1 << 32).is_ok());
+        assert_eq!(hints, [1 << 32]);
+    }
+}
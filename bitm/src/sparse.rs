@@ -0,0 +1,158 @@
+use std::iter::FusedIterator;
+use dyn_size_of::{GetSize, SizeReport};
+use super::{BitAccess, BitVec, Rank, BitArrayWithRank, BitArrayWithRankSelect, EliasFano, EliasFanoIterator};
+use super::select::last_satisfying;
+
+/// The structure that holds sparse array of bits and its rank structure.
+/// It stores only the positions of ones, using [`EliasFano`] encoding,
+/// which takes about *2+log2(u/n)* bits per one, where *u* is the length of the array and *n* is the number of ones.
+/// Thus, it takes much less space than dense structures (like [`ArrayWithRank101111`](crate::ArrayWithRank101111))
+/// for the arrays in which less than a few percent of bits are ones.
+///
+/// It returns the number of ones in first `index` bits of the array (see `rank` method)
+/// and the value of the bit at `index` (see `get_bit` method) in *O(log n)* time (*O(1)* expected for uniformly spread ones),
+/// and supports select (see `select1` and `select0` methods).
+#[derive(Clone)]
+pub struct ArrayWithRankSparse {
+    ones: EliasFano,    // positions of ones
+    len: usize  // number of 64-bit words of the content
+}
+
+impl GetSize for ArrayWithRankSparse {
+    #[inline] fn size_bytes_dyn(&self) -> usize { self.ones.size_bytes_dyn() }
+    const USES_DYN_MEM: bool = true;
+    fn size_report(&self) -> SizeReport { SizeReport::of(self).field("ones", &self.ones) }
+}
+
+impl ArrayWithRankSparse {
+    /// Returns the number of bits in the array.
+    #[inline] pub fn len(&self) -> usize { self.len * 64 }
+
+    /// Returns whether the array is empty.
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns the number of ones in the array.
+    #[inline] pub fn count_ones(&self) -> usize { self.ones.len() }
+
+    /// Returns the bit with given `index`.
+    #[inline] pub fn get_bit(&self, index: usize) -> bool {
+        self.ones.successor(index as u64).is_some_and(|(_, position)| position == index as u64)
+    }
+
+    /// Returns the number of ones in first `index` bits of the array.
+    #[inline] pub fn rank(&self, index: usize) -> u64 {
+        self.ones.lower_bound(index as u64) as u64
+    }
+
+    /// Returns the position of the `rank`-th one (counting from 0) in the array
+    /// or `None` if there are no more than `rank` ones in the array.
+    #[inline] pub fn select1(&self, rank: u64) -> Option<usize> {
+        self.ones.get(rank as usize).map(|position| position as usize)
+    }
+
+    /// Returns the position of the `rank`-th zero (counting from 0) in the array
+    /// or `None` if there are no more than `rank` zeros in the array.
+    pub fn select0(&self, rank: u64) -> Option<usize> {
+        // the number of ones before the zero is the number of ones preceded by at most rank zeros
+        let ones = last_satisfying(0, self.ones.len() + 1,
+            |i| i == 0 || self.ones.get(i - 1).unwrap() - (i - 1) as u64 <= rank);
+        let result = rank as usize + ones;
+        (result < self.len()).then_some(result)
+    }
+
+    /// Returns iterator over the positions of ones in the array.
+    #[inline] pub fn ones(&self) -> SparseOnesIterator<'_> {
+        SparseOnesIterator(self.ones.iter())
+    }
+
+    /// Returns the (decompressed) content of the array.
+    pub fn decompress(&self) -> Box<[u64]> {
+        let mut result = Box::<[u64]>::with_zeroed_64bit_segments(self.len);
+        for position in self.ones() { result.set_bit(position); }
+        result
+    }
+}
+
+impl BitArrayWithRank for ArrayWithRankSparse {
+    fn build(content: Box<[u64]>) -> (Self, u64) {
+        let ones: Vec<u64> = content.bit_ones().map(|position| position as u64).collect();
+        let count = ones.len() as u64;
+        (Self { ones: EliasFano::new(&ones), len: content.len() }, count)
+    }
+}
+
+impl Rank for ArrayWithRankSparse {
+    #[inline] fn rank(&self, index: usize) -> u64 {
+        Self::rank(self, index)
+    }
+
+    #[inline] fn get_bit(&self, index: usize) -> bool {
+        Self::get_bit(self, index)
+    }
+}
+
+impl BitArrayWithRankSelect for ArrayWithRankSparse {
+    #[inline] fn select1(&self, rank: u64) -> Option<usize> {
+        Self::select1(self, rank)
+    }
+
+    #[inline] fn select0(&self, rank: u64) -> Option<usize> {
+        Self::select0(self, rank)
+    }
+}
+
+/// Iterator over the positions of ones in [`ArrayWithRankSparse`].
+pub struct SparseOnesIterator<'a>(EliasFanoIterator<'a>);
+
+impl<'a> Iterator for SparseOnesIterator<'a> {
+    type Item = usize;
+
+    #[inline] fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|position| position as usize)
+    }
+
+    #[inline] fn size_hint(&self) -> (usize, Option<usize>) { self.0.size_hint() }
+}
+
+impl<'a> ExactSizeIterator for SparseOnesIterator<'a> {}
+
+impl<'a> FusedIterator for SparseOnesIterator<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArrayWithRank101111;
+
+    fn test_sparse(content: Box<[u64]>) {
+        let (a, ones) = ArrayWithRankSparse::build(content.clone());
+        let (dense, _) = ArrayWithRank101111::build(content.clone());
+        assert_eq!(ones, content.count_bit_ones() as u64);
+        assert_eq!(a.count_ones() as u64, ones);
+        assert_eq!(a.decompress(), content);
+        assert!(a.ones().eq(content.bit_ones()));
+        let mut zeros = 0;
+        for i in 0..content.len() * 64 {
+            assert_eq!(a.rank(i), dense.rank(i), "rank({})", i);
+            assert_eq!(Rank::get_bit(&a, i), content.get_bit(i));
+            if content.get_bit(i) {
+                assert_eq!(a.select1(a.rank(i)), Some(i));
+            } else {
+                assert_eq!(a.select0(zeros), Some(i), "select0({})", zeros);
+                zeros += 1;
+            }
+        }
+        assert_eq!(a.rank(content.len() * 64), ones);
+        assert_eq!(a.select1(ones), None);
+        assert_eq!(a.select0(zeros), None);
+    }
+
+    #[test]
+    fn rank_select() {
+        test_sparse(Box::new([]));
+        test_sparse(Box::new([0, 0]));
+        test_sparse(Box::new([1, 1 << 63, 0, u64::MAX]));
+        let content: Box<[u64]> = (0..500u64).map(|i| if i % 17 == 3 { 1 << (i % 64) } else { 0 }).collect();
+        test_sparse(content.clone());
+        assert!(ArrayWithRankSparse::build(content.clone()).0.size_bytes_dyn() < content.size_bytes_dyn());
+    }
+}
@@ -0,0 +1,208 @@
+use std::ops::Range;
+use dyn_size_of::{GetSize, SizeReport};
+use super::{BitAccess, BitVec, BitArrayWithRank, BitArrayWithRankSelect, ArrayWithRank101111};
+
+/// Level of [`WaveletMatrix`].
+#[derive(Clone)]
+struct Level<R> {
+    bits: R,    // bits of subsequent values at the level
+    zeros: usize    // number of zeros in bits
+}
+
+impl<R: BitArrayWithRankSelect> Level<R> {
+    /// Returns the number of ones among the first `index` bits.
+    #[inline] fn rank1(&self, index: usize) -> usize { self.bits.rank(index) as usize }
+
+    /// Returns the number of zeros among the first `index` bits.
+    #[inline] fn rank0(&self, index: usize) -> usize { index - self.rank1(index) }
+
+    /// Returns the index at the next level of the `index`-th item of the current level whose bit is `bit`.
+    #[inline] fn next_index(&self, index: usize, bit: bool) -> usize {
+        if bit { self.zeros + self.rank1(index) } else { self.rank0(index) }
+    }
+}
+
+impl<R: GetSize> GetSize for Level<R> {
+    #[inline] fn size_bytes_dyn(&self) -> usize { self.bits.size_bytes_dyn() }
+    const USES_DYN_MEM: bool = R::USES_DYN_MEM;
+}
+
+/// Wavelet matrix that stores a sequence of `bits_per_value`-bit integers (symbols)
+/// and supports `access`, `rank`, `select` and `quantile` queries in *O(bits_per_value)* time.
+///
+/// It stores one bit array (with rank and select structure of the type `R`) per each bit of the values.
+///
+/// See:
+/// - F. Claude, G. Navarro, A. Ordóñez, *The wavelet matrix: An efficient wavelet tree for large alphabets*,
+///   Information Systems 47 (2015), 15–32. <https://doi.org/10.1016/j.is.2014.06.002>
+#[derive(Clone)]
+pub struct WaveletMatrix<R = ArrayWithRank101111> {
+    levels: Box<[Level<R>]>,    // the first level stores the most significant bits
+    len: usize
+}
+
+impl<R: GetSize> GetSize for WaveletMatrix<R> {
+    #[inline] fn size_bytes_dyn(&self) -> usize { self.levels.size_bytes_dyn() }
+    const USES_DYN_MEM: bool = true;
+    fn size_report(&self) -> SizeReport {
+        self.levels.iter().enumerate().fold(SizeReport::of(self), |r, (i, l)| r.field(format!("level {}", i), &l.bits))
+    }
+}
+
+impl<R: BitArrayWithRank + BitArrayWithRankSelect> WaveletMatrix<R> {
+    /// Constructs the wavelet matrix that stores `bits_per_value` (up to 64) lowest bits of each of the given `values`.
+    pub fn new(values: &[u64], bits_per_value: u8) -> Self {
+        assert!(bits_per_value <= 64, "WaveletMatrix supports up to 64 bits per value");
+        let len = values.len();
+        let mut current = values.to_vec();
+        let mut next = Vec::with_capacity(len);
+        let mut levels = Vec::with_capacity(bits_per_value as usize);
+        for bit in (0..bits_per_value).rev() {
+            let mut bits = Box::<[u64]>::with_zeroed_bits(len + 1);    // +1 makes rank(len) valid
+            for (i, v) in current.iter().enumerate() {
+                if v & (1 << bit) != 0 { bits.set_bit(i); }
+            }
+            next.clear();
+            next.extend(current.iter().filter(|v| *v & (1 << bit) == 0));
+            let zeros = next.len();
+            next.extend(current.iter().filter(|v| *v & (1 << bit) != 0));
+            std::mem::swap(&mut current, &mut next);
+            levels.push(Level { bits: R::build(bits).0, zeros });
+        }
+        Self { levels: levels.into_boxed_slice(), len }
+    }
+
+    /// Returns the number of values stored.
+    #[inline] pub fn len(&self) -> usize { self.len }
+
+    /// Returns whether no values are stored.
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Returns the number of bits used to store each value.
+    #[inline] pub fn bits_per_value(&self) -> u8 { self.levels.len() as u8 }
+
+    /// Returns the value with given `index` or `None` if `index` is out of bounds.
+    pub fn access(&self, mut index: usize) -> Option<u64> {
+        if index >= self.len { return None; }
+        let mut result = 0;
+        for level in self.levels.iter() {
+            let bit = level.rank1(index + 1) != level.rank1(index);
+            result = (result << 1) | bit as u64;
+            index = level.next_index(index, bit);
+        }
+        Some(result)
+    }
+
+    /// Returns the range of positions occupied by the `symbol` at the last level,
+    /// restricted to the first `end` values of the sequence.
+    #[inline] fn symbol_range(&self, symbol: u64, mut end: usize) -> Range<usize> {
+        let mut begin = 0;
+        for (level, bit) in self.levels.iter().zip((0..self.levels.len()).rev()) {
+            let bit = symbol & (1 << bit) != 0;
+            begin = level.next_index(begin, bit);
+            end = level.next_index(end, bit);
+        }
+        begin..end
+    }
+
+    /// Returns the number of occurrences of the `symbol` among the first `index` (at most `self.len()`) values.
+    pub fn rank(&self, symbol: u64, index: usize) -> usize {
+        if self.bits_per_value() < 64 && symbol >> self.bits_per_value() != 0 { return 0; }
+        self.symbol_range(symbol, index.min(self.len)).len()
+    }
+
+    /// Returns the position of the `rank`-th (counting from 0) occurrence of the `symbol`
+    /// or `None` if the `symbol` occurs no more than `rank` times.
+    pub fn select(&self, symbol: u64, rank: usize) -> Option<usize> {
+        if self.bits_per_value() < 64 && symbol >> self.bits_per_value() != 0 { return None; }
+        let range = self.symbol_range(symbol, self.len);
+        if rank >= range.len() { return None; }
+        let mut index = range.start + rank;
+        for (level, bit) in self.levels.iter().rev().zip(0..self.levels.len()) {
+            index = if symbol & (1 << bit) != 0 {
+                level.bits.select1((index - level.zeros) as u64)?
+            } else {
+                level.bits.select0(index as u64)?
+            };
+        }
+        Some(index)
+    }
+
+    /// Returns the `k`-th (counting from 0) smallest value among the values with indices in the given `range`,
+    /// or `None` if the `range` contains no more than `k` values.
+    pub fn quantile(&self, range: Range<usize>, mut k: usize) -> Option<u64> {
+        let (mut begin, mut end) = (range.start, range.end.min(self.len));
+        if begin >= end || k >= end - begin { return None; }
+        let mut result = 0;
+        for level in self.levels.iter() {
+            let zeros = level.rank0(end) - level.rank0(begin);
+            let bit = k >= zeros;
+            if bit { k -= zeros; }
+            result = (result << 1) | bit as u64;
+            begin = level.next_index(begin, bit);
+            end = level.next_index(end, bit);
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArrayWithRankSimple, ArrayWithRankRRR, ArrayWithRankSparse};
+
+    fn test_wm<R: BitArrayWithRank + BitArrayWithRankSelect>(values: &[u64], bits_per_value: u8) {
+        let wm = WaveletMatrix::<R>::new(values, bits_per_value);
+        assert_eq!(wm.len(), values.len());
+        for (i, v) in values.iter().enumerate() {
+            assert_eq!(wm.access(i), Some(*v));
+        }
+        assert_eq!(wm.access(values.len()), None);
+        for symbol in 0..(1 << bits_per_value) + 1 {
+            let positions: Vec<usize> = (0..values.len()).filter(|i| values[*i] == symbol).collect();
+            for i in 0..=values.len() {
+                assert_eq!(wm.rank(symbol, i), positions.iter().filter(|p| **p < i).count());
+            }
+            for (rank, position) in positions.iter().enumerate() {
+                assert_eq!(wm.select(symbol, rank), Some(*position));
+            }
+            assert_eq!(wm.select(symbol, positions.len()), None);
+        }
+        for begin in 0..values.len() {
+            for end in begin..=values.len() {
+                let mut sorted = values[begin..end].to_vec();
+                sorted.sort();
+                for k in 0..=sorted.len() {
+                    assert_eq!(wm.quantile(begin..end, k), sorted.get(k).copied());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn small() {
+        test_wm::<ArrayWithRank101111>(&[], 3);
+        test_wm::<ArrayWithRank101111>(&[4, 7, 6, 5, 3, 2, 1, 0, 1, 4, 1, 7], 3);
+        test_wm::<ArrayWithRankSimple>(&[4, 7, 6, 5, 3, 2, 1, 0, 1, 4, 1, 7], 3);
+        test_wm::<ArrayWithRankRRR>(&[4, 7, 6, 5, 3, 2, 1, 0, 1, 4, 1, 7], 3);
+        test_wm::<ArrayWithRankSparse>(&[4, 7, 6, 5, 3, 2, 1, 0, 1, 4, 1, 7], 3);
+        test_wm::<ArrayWithRank101111>(&[1, 0, 1, 1, 0], 1);
+    }
+
+    #[test]
+    fn bigger() {
+        let values: Vec<u64> = (0..200u64).map(|i| (i * 37 + i / 7) % 13).collect();
+        test_wm::<ArrayWithRank101111>(&values, 4);
+    }
+
+    #[test]
+    fn full_width() {
+        let values = [u64::MAX, 0, 1 << 63, 5, u64::MAX];
+        let wm = WaveletMatrix::<ArrayWithRank101111>::new(&values, 64);
+        assert_eq!(wm.access(0), Some(u64::MAX));
+        assert_eq!(wm.access(2), Some(1 << 63));
+        assert_eq!(wm.rank(u64::MAX, 5), 2);
+        assert_eq!(wm.select(u64::MAX, 1), Some(4));
+        assert_eq!(wm.quantile(0..5, 2), Some(1 << 63));
+    }
+}
@@ -1,5 +1,5 @@
 use std::hash::Hash;
-use binout::{VByte, AsIs, Serializer};
+use binout::{VByte, AsIs, Header, Serializer};
 use minimum_redundancy::DecodingResult;
 use bitm::{BitAccess, BitVec, BitArrayWithRank};
 use crate::fp::level_size_chooser::LevelSizeChooser;
@@ -15,7 +15,8 @@ pub use conf::CMapConf;
 use crate::fp::collision_solver::{CollisionSolver, CollisionSolverBuilder, IsLossless};
 
 use crate::fp::common::{encode_all, encode_all_from_map};
-use dyn_size_of::GetSize;
+use crate::MAGIC;
+use dyn_size_of::{GetSize, SizeReport};
 use crate::coding::{Coding, Decoder, SerializableCoding, BuildCoding};
 
 /// Finger-Printing based static function (map) with compressed values.
@@ -35,6 +36,12 @@ impl<C: GetSize, S> GetSize for CMap<C, S> {
             + self.value_coding.size_bytes_dyn()
     }
     const USES_DYN_MEM: bool = true;
+    fn size_report(&self) -> SizeReport {
+        SizeReport::of(self).field("array", &self.array)
+            .field("value_fragments", &self.value_fragments)
+            .field("level_sizes", &self.level_sizes)
+            .field("value_coding", &self.value_coding)
+    }
 }
 
 impl<C, S: BuildSeededHasher> CMap<C, S> {
@@ -52,7 +59,7 @@ impl<C: Coding, S: BuildSeededHasher> CMap<C, S> {
         loop {
             let level_size = (*self.level_sizes.get(level as usize)? as usize) << 6usize;
             let i = array_begin_index + self.index(k, level, level_size);
-            if self.array.content.get_bit(i) {
+            if self.array.get_bit(i) {
                 match result_decoder.consume(self.value_fragments.get_fragment(self.array.rank(i) as usize, self.value_coding.bits_per_fragment()) as u8) {
                     DecodingResult::Value(v) => {
                         access_stats.found_on_level(level);
@@ -146,7 +153,7 @@ impl<C: Coding, S: BuildSeededHasher> CMap<C, S> {
             loop {
                 let level_size = (levels[level as usize] as usize) << 6usize;
                 let i = array_begin_index + utils::map64_to_64(conf.hash.hash_one(&keys[input_index], level), level_size as u64) as usize;
-                if array.content.get_bit(i) {
+                if array.get_bit(i) {
                     let code = &mut values[input_index];
                     output_value_fragments.init_fragment(   // AcceptEquals::set_value
                                                             array.rank(i) as usize,
@@ -174,10 +181,14 @@ impl<C: Coding, S: BuildSeededHasher> CMap<C, S> {
 
 impl<C: SerializableCoding, S: BuildSeededHasher> CMap<C, S> {
 
+    /// Header written at the beginning of serialized [`CMap`].
+    const HEADER: Header = Header::new(MAGIC, 2, 1, S::ID);
+
     /// Returns number of bytes which `write` will write, assuming that each call to `write_value` writes `bytes_per_value` bytes.
     pub fn write_bytes(&self, bytes_per_value: usize) -> usize {
-        VByte::array_size(&self.level_sizes)
-            + AsIs::array_content_size(&self.array.content)
+        Header::SIZE
+            + VByte::array_size(&self.level_sizes)
+            + self.array.content_words().len() * std::mem::size_of::<u64>()
             + self.value_coding.write_bytes(bytes_per_value)
             + AsIs::array_content_size(&self.value_fragments)
     }
@@ -186,19 +197,24 @@ impl<C: SerializableCoding, S: BuildSeededHasher> CMap<C, S> {
     pub fn write<F>(&self, output: &mut dyn io::Write, write_value: F) -> io::Result<()>
         where F: FnMut(&mut dyn io::Write, &C::Value) -> io::Result<()>
     {
+        Self::HEADER.write(output)?;
         VByte::write_array(output, &self.level_sizes)?;
-        AsIs::write_all(output, self.array.content.iter())?;
+        AsIs::write_all_values(output, self.array.content_words())?;
         self.value_coding.write(output, write_value)?;
         AsIs::write_all(output, self.value_fragments.iter())
     }
 
     /// Read self from the input, using read_value to read values (hasher must be the same as used by written `BBMap`).
+    ///
+    /// Returns [`io::ErrorKind::InvalidData`] error if the `input` does not begin with the header of [`CMap`]
+    /// serialized in the supported format with the same hasher (which is checked only for hashers with non-zero IDs).
     pub fn read_with_hasher<F>(input: &mut dyn io::Read, read_value: F, hasher: S) -> io::Result<Self>
         where F: FnMut(&mut dyn io::Read) -> io::Result<C::Value>
     {
+        Self::HEADER.read_expected(input)?;
         let level_sizes = VByte::read_array(input)?;
         let array_content = AsIs::read_n(input, level_sizes.iter().map(|v|*v as usize).sum::<usize>())?;
-        let (array_with_rank, number_of_ones) = ArrayWithRank::build(array_content);
+        let (array_with_rank, number_of_ones) = utils::build_array_with_rank_par(array_content);
         let value_coding = C::read(input, read_value)?;
         let value_fragments = read_bits(input, number_of_ones as usize * value_coding.bits_per_fragment() as usize)?;
         Ok(Self {
@@ -315,14 +331,14 @@ mod tests {
         bbmap.write(&mut buff, |b, v| AsIs::write(b, *v)).unwrap();
         assert_eq!(buff.len(), bbmap.write_bytes(1));
         let read = CMap::<C>::read(&mut &buff[..], |b| AsIs::read(b)).unwrap();
-        assert_eq!(bbmap.array.content, read.array.content);
+        assert_eq!(bbmap.array.content_words().collect::<Vec<_>>(), read.array.content_words().collect::<Vec<_>>());
         assert_eq!(bbmap.level_sizes, read.level_sizes);
     }
 
     fn test_bbmap_invariants<C: Coding>(bbmap: &CMap<C>) {
-        assert_eq!(bbmap.level_sizes.iter().map(|v|*v as usize).sum::<usize>(), bbmap.array.content.len());
+        assert_eq!(bbmap.level_sizes.iter().map(|v|*v as usize).sum::<usize>(), bbmap.array.content_words().len());
         assert_eq!(
-            ceiling_div(bbmap.array.content.iter().map(|v|v.count_ones()).sum::<u32>() as usize * bbmap.value_coding.bits_per_fragment() as usize, 64),
+            ceiling_div(bbmap.array.content_words().map(|v|v.count_ones()).sum::<u32>() as usize * bbmap.value_coding.bits_per_fragment() as usize, 64),
             bbmap.value_fragments.len()
         );
     }
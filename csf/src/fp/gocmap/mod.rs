@@ -1,6 +1,6 @@
 use std::hash::Hash;
-use binout::{VByte, Serializer, AsIs};
-use ph::utils::read_bits;
+use binout::{VByte, Serializer, AsIs, Header};
+use ph::utils::{read_bits, build_array_with_rank_par};
 use ph::{BuildDefaultSeededHasher, BuildSeededHasher, stats, utils::ArrayWithRank};
 use bitm::{BitAccess, BitArrayWithRank, BitVec};
 use minimum_redundancy::DecodingResult;
@@ -14,8 +14,9 @@ mod conf;
 pub use conf::GOCMapConf;
 use ph::fmph::{goindexing::group_nr, GroupSize, SeedSize, TwoToPowerBitsStatic};
 pub use ph::fmph::GOConf;
-use dyn_size_of::GetSize;
+use dyn_size_of::{GetSize, SizeReport};
 use crate::coding::{Coding, Decoder, SerializableCoding, BuildCoding};
+use crate::MAGIC;
 
 
 /// Finger-Printing based static function (map) with compressed values and group optimization.
@@ -38,6 +39,14 @@ impl<C: GetSize, GS: GroupSize, SS: SeedSize, S> GetSize for GOCMap<C, GS, SS, S
     }
 
     const USES_DYN_MEM: bool = true;
+
+    fn size_report(&self) -> SizeReport {
+        SizeReport::of(self).field("array", &self.array)
+            .field("value_fragments", &self.value_fragments)
+            .field("group_seeds", &self.group_seeds)
+            .field("level_size", &self.level_size)
+            .field("value_coding", &self.value_coding)
+    }
 }
 
 impl<C: Coding, GS: GroupSize, SS: SeedSize, S: BuildSeededHasher> GOCMap<C, GS, SS, S> {
@@ -80,7 +89,7 @@ impl<C: Coding, GS: GroupSize, SS: SeedSize, S: BuildSeededHasher> GOCMap<C, GS,
             let group = groups_before + group_nr(hash, level_size_groups);
             let seed = self.goconf.bits_per_seed.get_seed(&self.group_seeds, group as usize);
             let i = self.goconf.bits_per_group.bit_index_for_seed(hash, seed, group);
-            if self.array.content.get_bit(i) {
+            if self.array.get_bit(i) {
                 match result_decoder.consume(self.value_fragments.get_fragment(self.array.rank(i) as usize, self.value_coding.bits_per_fragment()) as u8) {
                     DecodingResult::Value(v) => {
                         access_stats.found_on_level(level_nr);
@@ -191,7 +200,7 @@ impl<C: Coding, GS: GroupSize, SS: SeedSize, S: BuildSeededHasher> GOCMap<C, GS,
                 let hash = conf.goconf.hash_builder.hash_one(&keys[input_index], level_nr);
                 let group = groups_before + group_nr(hash, level_size_groups);
                 let i = conf.goconf.bits_per_group.bit_index_for_seed(hash, conf.goconf.bits_per_seed.get_seed(&group_seeds, group as usize), group);
-                if array.content.get_bit(i) {
+                if array.get_bit(i) {
                     let code = &mut values[input_index];
                     output_value_fragments.init_fragment(   // AcceptEquals::set_value
                                                             array.rank(i) as usize,
@@ -219,11 +228,15 @@ impl<C: Coding, GS: GroupSize, SS: SeedSize, S: BuildSeededHasher> GOCMap<C, GS,
 }
 
 impl<C: SerializableCoding, S: BuildSeededHasher, GS: GroupSize, SS: SeedSize> GOCMap<C, GS, SS, S> {
+    /// Header written at the beginning of serialized [`GOCMap`].
+    const HEADER: Header = Header::new(MAGIC, 3, 1, S::ID);
+
     /// Returns number of bytes which `write` will write, assuming that each call to `write_value` writes `bytes_per_value` bytes.
     pub fn write_bytes(&self, bytes_per_value: usize) -> usize {
-        2*std::mem::size_of::<u8>()
+        Header::SIZE
+            + 2*std::mem::size_of::<u8>()
             + VByte::array_size(&self.level_size)
-            + AsIs::array_content_size(&self.array.content)
+            + self.array.content_words().len() * std::mem::size_of::<u64>()
             + self.group_seeds.size_bytes_dyn()
             + self.value_coding.write_bytes(bytes_per_value)
             + AsIs::array_content_size(&self.value_fragments)
@@ -233,9 +246,10 @@ impl<C: SerializableCoding, S: BuildSeededHasher, GS: GroupSize, SS: SeedSize> G
     pub fn write<F>(&self, output: &mut dyn io::Write, write_value: F) -> io::Result<()>
         where F: FnMut(&mut dyn io::Write, &C::Value) -> io::Result<()>
     {
+        Self::HEADER.write(output)?;
         self.goconf.bits_per_group.write(output)?;
         VByte::write_array(output, &self.level_size)?;
-        AsIs::write_all(output, self.array.content.iter())?;
+        AsIs::write_all_values(output, self.array.content_words())?;
         self.goconf.bits_per_seed.write_seed_vec(output, &self.group_seeds)?;
         self.value_coding.write(output, write_value)?;
         AsIs::write_all(output, self.value_fragments.iter())
@@ -243,15 +257,19 @@ impl<C: SerializableCoding, S: BuildSeededHasher, GS: GroupSize, SS: SeedSize> G
 
     /// Reads `Self` from the `input`, using `read_value` to read values.
     /// Hasher must be the same as the one used to write.
+    ///
+    /// Returns [`io::ErrorKind::InvalidData`] error if the `input` does not begin with the header of [`GOCMap`]
+    /// serialized in the supported format with the same hasher (which is checked only for hashers with non-zero IDs).
     pub fn read_with_hasher<F>(input: &mut dyn io::Read, read_value: F, hasher: S) -> io::Result<Self>
         where F: FnMut(&mut dyn io::Read) -> io::Result<C::Value>
     {
+        Self::HEADER.read_expected(input)?;
         let bits_per_group = GS::read(input)?;
         let level_size = VByte::read_array(input)?;
         let number_of_groups = level_size.iter().map(|v|*v as usize).sum::<usize>();
 
         let array_content = read_bits(input, bits_per_group * number_of_groups)?;
-        let (array_with_rank, number_of_ones) = ArrayWithRank::build(array_content);
+        let (array_with_rank, number_of_ones) = build_array_with_rank_par(array_content);
 
         let (bits_per_seed, group_seeds) = SS::read_seed_vec(input, number_of_groups)?;
 
@@ -376,7 +394,7 @@ mod tests {
         assert_eq!(buff.len(), bbmap.write_bytes(1));
         let read = GOCMap::<C, GS, SS>::read(&mut &buff[..], |b| AsIs::read(b)).unwrap();
         assert_eq!(bbmap.level_size, read.level_size);
-        assert_eq!(bbmap.array.content, read.array.content);
+        assert_eq!(bbmap.array.content_words().collect::<Vec<_>>(), read.array.content_words().collect::<Vec<_>>());
         assert_eq!(bbmap.group_seeds, read.group_seeds);
         assert_eq!(bbmap.value_fragments, read.value_fragments);
         assert_eq!(bbmap.goconf.bits_per_group.into(), read.goconf.bits_per_group.into());
@@ -385,10 +403,10 @@ mod tests {
 
     fn test_bbmap2_invariants<GS: GroupSize, SS: SeedSize, C: Coding>(bbmap: &GOCMap<C, GS, SS>) {
         let number_of_groups = bbmap.level_size.iter().map(|v| *v as usize).sum::<usize>();
-        assert_eq!(bbmap.goconf.bits_per_group * number_of_groups, bbmap.array.content.len() * 64);
+        assert_eq!(bbmap.goconf.bits_per_group * number_of_groups, bbmap.array.content_words().len() * 64);
         assert_eq!(ceiling_div(bbmap.goconf.bits_per_seed.into() as usize * number_of_groups, 64), bbmap.group_seeds.len());
         assert_eq!(
-            ceiling_div(bbmap.array.content.iter().map(|v|v.count_ones()).sum::<u32>() as usize * bbmap.value_coding.bits_per_fragment() as usize, 64),
+            ceiling_div(bbmap.array.content_words().map(|v|v.count_ones()).sum::<u32>() as usize * bbmap.value_coding.bits_per_fragment() as usize, 64),
             bbmap.value_fragments.len()
         );
     }
@@ -401,6 +419,10 @@ mod tests {
         assert_eq!(bbmap.get(&'d'), Some(&3));
         test_bbmap2_invariants(&bbmap);
         test_read_write(&bbmap);
+        let report = bbmap.size_report();
+        assert_eq!(report.bytes, bbmap.size_bytes());
+        assert_eq!(report.children.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+            ["array", "value_fragments", "group_seeds", "level_size", "value_coding"]);
     }
 
     #[test]
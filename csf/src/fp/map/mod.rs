@@ -1,5 +1,5 @@
 mod conf;
-use binout::{AsIs, Serializer, VByte};
+use binout::{AsIs, Header, Serializer, VByte};
 pub use conf::MapConf;
 
 use std::hash::Hash;
@@ -10,8 +10,8 @@ use ph::{BuildDefaultSeededHasher, BuildSeededHasher, utils, stats, utils::{Arra
 use std::collections::HashMap;
 use std::io;
 
-use crate::{fp::collision_solver::{CollisionSolver, CollisionSolverBuilder}, bits_to_store};
-use dyn_size_of::GetSize;
+use crate::{fp::collision_solver::{CollisionSolver, CollisionSolverBuilder}, bits_to_store, MAGIC};
+use dyn_size_of::{GetSize, SizeReport};
 
 /// Finger-Printing based static function (map) that can only store integer values of given bit-size.
 pub struct Map<S = BuildDefaultSeededHasher> {
@@ -30,6 +30,12 @@ impl<S: BuildSeededHasher> GetSize for Map<S> {
     }
 
     const USES_DYN_MEM: bool = true;
+
+    fn size_report(&self) -> SizeReport {
+        SizeReport::of(self).field("array", &self.array)
+            .field("values", &self.values)
+            .field("level_sizes", &self.level_sizes)
+    }
 }
 
 impl<S: BuildSeededHasher> Map<S> {
@@ -45,7 +51,7 @@ impl<S: BuildSeededHasher> Map<S> {
         loop {
             let level_size = (*self.level_sizes.get(level as usize)? as usize) << 6usize;
             let i = array_begin_index + self.index(k, level, level_size);
-            if self.array.content.get_bit(i) {
+            if self.array.get_bit(i) {
                 access_stats.found_on_level(level);
                 return Some(self.values.get_fragment(self.array.rank(i) as usize, self.bits_per_value));
             }
@@ -121,7 +127,7 @@ impl<S: BuildSeededHasher> Map<S> {
             loop {
                 let level_size = (level_sizes[level as usize] as usize) << 6usize;
                 let i = array_begin_index + utils::map64_to_64(conf.hash.hash_one(&keys[input_index], level), level_size as u64) as usize;
-                if array.content.get_bit(i) {
+                if array.get_bit(i) {
                     CSB::CollisionSolver::set_value(&mut output_value_fragments, array.rank(i) as usize, values[input_index], conf.bits_per_value);
                     // stats.value_on_level(level); // TODO do we need this? we can get average levels from lookups
                     break;
@@ -147,30 +153,39 @@ impl<S: BuildSeededHasher> Map<S> {
         Self::with_slices_conf_stats(keys, values, conf, &mut ())
     }
 
+    /// Header written at the beginning of serialized [`Map`].
+    const HEADER: Header = Header::new(MAGIC, 1, 1, S::ID);
+
     /// Returns number of bytes which write will write.
     pub fn write_bytes(&self) -> usize {
+        Header::SIZE +
         AsIs::size(self.bits_per_value) +
         VByte::array_size(&self.level_sizes) +
-        AsIs::array_content_size(&self.array.content) +
+        self.array.content_words().len() * std::mem::size_of::<u64>() +
         AsIs::array_content_size(&self.values)
     }
 
     /// Write `self` to the output.
     pub fn write(&self, output: &mut dyn io::Write) -> io::Result<()>
     {
+        Self::HEADER.write(output)?;
         AsIs::write(output, self.bits_per_value)?;
         VByte::write_array(output, &self.level_sizes)?;
-        AsIs::write_all(output, self.array.content.iter())?;
+        AsIs::write_all_values(output, self.array.content_words())?;
         AsIs::write_all(output, self.values.iter())
     }
 
     /// Read `self` from the `input` (`hasher` must be the same as used by written [`Map`]).
+    ///
+    /// Returns [`io::ErrorKind::InvalidData`] error if the `input` does not begin with the header of [`Map`]
+    /// serialized in the supported format with the same hasher (which is checked only for hashers with non-zero IDs).
     pub fn read_with_hasher(input: &mut dyn io::Read, hasher: S) -> io::Result<Self>
     {
+        Self::HEADER.read_expected(input)?;
         let bits_per_value = AsIs::read(input)?;
         let level_sizes = VByte::read_array(input)?;
         let array_content = AsIs::read_n(input, level_sizes.iter().map(|v|*v as usize).sum::<usize>())?;
-        let (array_with_rank, number_of_ones) = ArrayWithRank::build(array_content);
+        let (array_with_rank, number_of_ones) = utils::build_array_with_rank_par(array_content);
         let values = read_bits(input, number_of_ones as usize * bits_per_value as usize)?;
         Ok(Self {
             array: array_with_rank,
@@ -232,6 +247,7 @@ mod tests {
     use super::*;
     use bitm::ceiling_div;
     use maplit::hashmap;
+    use crate::tests::test_get_size_against_derived;
 
     fn test_read_write(bbmap: &Map) {
         let mut buff = Vec::new();
@@ -239,12 +255,29 @@ mod tests {
         assert_eq!(buff.len(), bbmap.write_bytes());
         let read = Map::read(&mut &buff[..]).unwrap();
         assert_eq!(bbmap.level_sizes, read.level_sizes);
+        let other = crate::ls::Map::read(&mut &buff[..]);
+        assert_eq!(other.err().map(|e| e.kind()), Some(io::ErrorKind::InvalidData));
+        let other = ph::fmph::Function::read(&mut &buff[..]);
+        assert_eq!(other.err().map(|e| e.kind()), Some(io::ErrorKind::InvalidData));
+    }
+
+    fn test_get_size(bbmap: Map) {
+        #[derive(GetSize)]
+        struct Derived {
+            array: ArrayWithRank,
+            values: Box<[u64]>,
+            bits_per_value: u8,
+            level_sizes: Box<[u32]>,
+            #[get_size(static)] _hash_builder: BuildDefaultSeededHasher
+        }
+        test_get_size_against_derived(bbmap, |Map { array, values, bits_per_value, level_sizes, hash_builder }|
+            Derived { array, values, bits_per_value, level_sizes, _hash_builder: hash_builder });
     }
 
     fn test_bbmap_invariants(bbmap: &Map) {
-        assert_eq!(bbmap.level_sizes.iter().map(|v| *v as usize).sum::<usize>(), bbmap.array.content.len());
+        assert_eq!(bbmap.level_sizes.iter().map(|v| *v as usize).sum::<usize>(), bbmap.array.content_words().len());
         assert_eq!(
-            ceiling_div(bbmap.array.content.iter().map(|v|v.count_ones()).sum::<u32>() as usize * bbmap.bits_per_value as usize, 64),
+            ceiling_div(bbmap.array.content_words().map(|v|v.count_ones()).sum::<u32>() as usize * bbmap.bits_per_value as usize, 64),
             bbmap.values.len()
         );
     }
@@ -257,6 +290,7 @@ mod tests {
         assert_eq!(bbmap.get(&'d'), Some(3));
         test_bbmap_invariants(&bbmap);
         test_read_write(&bbmap);
+        test_get_size(bbmap);
     }
 
     #[test]
@@ -6,3 +6,21 @@ pub mod fp;
 pub mod ls;
 
 pub use dyn_size_of::GetSize;
+
+/// Magic bytes that begin the [`Header`](binout::Header) of each structure serialized by this crate.
+pub(crate) const MAGIC: [u8; 4] = *b"BScs";
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::GetSize;
+
+    /// Checks the hand-written [`GetSize`] implementation of `value` against the derived one of the structure
+    /// (with the same fields as `value`) returned by `into_derived`.
+    pub fn test_get_size_against_derived<T: GetSize, D: GetSize>(value: T, into_derived: impl FnOnce(T) -> D) {
+        let (size_dyn, size_content_dyn) = (value.size_bytes_dyn(), value.size_bytes_content_dyn());
+        let derived = into_derived(value);
+        assert_eq!(derived.size_bytes_dyn(), size_dyn);
+        assert_eq!(derived.size_bytes_content_dyn(), size_content_dyn);
+        assert_eq!(D::USES_DYN_MEM, T::USES_DYN_MEM);
+    }
+}
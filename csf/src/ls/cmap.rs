@@ -1,14 +1,15 @@
 use std::io;
-use std::hash::{BuildHasherDefault, Hash};
-use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
 use super::Map;
 use crate::coding::{Coding, Decoder, SerializableCoding, BuildCoding};
 use super::conf::{MapConf, ValuesPreFiller};
 use bitm::{BitAccess, BitVec};
+use binout::Header;
+use crate::MAGIC;
 use ph::stats::AccessStatsCollector;
 use ph::{BuildDefaultSeededHasher, BuildSeededHasher};
 use std::collections::HashMap;
-use dyn_size_of::GetSize;
+use dyn_size_of::{GetSize, SizeReport};
 use minimum_redundancy::{BitsPerFragment, DecodingResult};
 
 /*pub struct KeyCodesIterator<'k, Key, Value, KeyValueIterator>
@@ -129,34 +130,45 @@ impl<C: GetSize, S> GetSize for CMap<C, S> {
         self.value_fragments.size_bytes_dyn() + self.value_coding.size_bytes_dyn()
     }
     const USES_DYN_MEM: bool = Map::<S>::USES_DYN_MEM || C::USES_DYN_MEM;
+    fn size_report(&self) -> SizeReport {
+        SizeReport::of(self).field("value_fragments", &self.value_fragments).field("value_coding", &self.value_coding)
+    }
 }
 
-impl<C: SerializableCoding, S> CMap<C, S> {
+impl<C: SerializableCoding, S: BuildSeededHasher> CMap<C, S> {
+    /// Header written at the beginning of serialized [`CMap`].
+    const HEADER: Header = Header::new(MAGIC, 5, 1, S::ID);
+
     /// Returns the number of bytes which `write` will write, assuming that each call to `write_value` writes `bytes_per_value` bytes.
     pub fn write_bytes(&self, bytes_per_value: usize) -> usize {
-        self.value_fragments.write_bytes() + self.value_coding.write_bytes(bytes_per_value)
+        Header::SIZE + self.value_fragments.content_write_bytes() + self.value_coding.write_bytes(bytes_per_value)
     }
 
     /// Writes `self` to the `output` using `write_value` to write values.
     pub fn write<F>(&self, output: &mut dyn io::Write, write_value: F) -> io::Result<()>
         where F: FnMut(&mut dyn io::Write, &C::Value) -> io::Result<()>
     {
-        self.value_fragments.write(output)?;
+        Self::HEADER.write(output)?;
+        self.value_fragments.write_content(output)?;
         self.value_coding.write(output, write_value)
     }
 
     /// Reads `self` from the input, using `read_value` to read values (`hasher` must be the same as used by stored `BDZHMap`).
+    ///
+    /// Returns [`io::ErrorKind::InvalidData`] error if the `input` does not begin with the header of [`CMap`]
+    /// serialized in the supported format with the same hasher (which is checked only for hashers with non-zero IDs).
     pub fn read_with_hasher<F>(input: &mut dyn io::Read, read_value: F, hasher: S) -> io::Result<Self>
         where F: FnMut(&mut dyn io::Read) -> io::Result<C::Value>
     {
+        Self::HEADER.read_expected(input)?;
         Ok(Self {
-            value_fragments: Map::<S>::read_with_hasher(input, hasher)?,
+            value_fragments: Map::<S>::read_content_with_hasher(input, hasher)?,
             value_coding: C::read(input, read_value)?
         })
     }
 }
 
-impl<C: SerializableCoding> CMap<C, BuildHasherDefault<DefaultHasher>> {
+impl<C: SerializableCoding> CMap<C> {
     /// Reads `BDZHMap` from the input using `read_value` to read values.
     /// Only `BDZHMap`s that use default hasher can be read by this method.
     pub fn read<F>(input: &mut dyn io::Read, read_value: F) -> io::Result<Self>
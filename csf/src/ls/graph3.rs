@@ -1,6 +1,6 @@
 use std::ops::BitXorAssign;
 
-use bitm::{BitAccess, BitVec};
+pub use bitm::PackedInts;
 
 pub trait VertexIndex: BitXorAssign + Default + Copy + Sized {
     fn from_usize(u: usize) -> Self;
@@ -90,29 +90,17 @@ impl EdgeValues for () {
     #[inline(always)] fn get_value(&self, _vertex: usize) -> Self::Value {}
 }
 
-/// Stores values of a specific (in bits) size.
-pub struct PackedInts {
-    values: Box<[u64]>,
-    bits_per_value: u8
-}
-
-impl PackedInts {
-    pub fn new(number_of_values: usize, bits_per_value: u8) -> Self {
-        Self { values: Box::with_zeroed_bits(number_of_values * bits_per_value as usize), bits_per_value }
-    }
-}
-
 impl EdgeValues for PackedInts {
     type Value = u64;
 
     fn add_or_remove_value(&mut self, a: usize, b: usize, c: usize, value: Self::Value) {
-        self.values.xor_fragment(a, value, self.bits_per_value);
-        self.values.xor_fragment(b, value, self.bits_per_value);
-        self.values.xor_fragment(c, value, self.bits_per_value);
+        self.xor(a, value);
+        self.xor(b, value);
+        self.xor(c, value);
     }
 
     fn get_value(&self, vertex: usize) -> Self::Value {
-        self.values.get_fragment(vertex, self.bits_per_value)
+        self.get(vertex).unwrap()
     }
 }
 
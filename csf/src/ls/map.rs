@@ -1,12 +1,12 @@
-use binout::{AsIs, Serializer, VByte};
+use binout::{AsIs, Header, Serializer, VByte};
 use bitm::{BitAccess, ceiling_div, BitVec};
 use ph::{BuildSeededHasher, BuildDefaultSeededHasher, utils::map64_to_64};
 use std::hash::Hash;
 use std::collections::HashMap;
 use std::io;
 use std::borrow::Borrow;
-use dyn_size_of::GetSize;
-use crate::bits_to_store;
+use dyn_size_of::{GetSize, SizeReport};
+use crate::{bits_to_store, MAGIC};
 
 use super::graph3::{HyperGraph, VertexIndex};
 use super::conf::{MapConf, ValuesPreFiller};
@@ -38,27 +38,55 @@ impl<S> GetSize for Map<S> {
         self.values.size_bytes_dyn()
     }
     const USES_DYN_MEM: bool = true;
+    fn size_report(&self) -> SizeReport {
+        SizeReport::of(self).field("values", &self.values)
+    }
 }
 
-impl<S> Map<S> {
+impl<S: BuildSeededHasher> Map<S> {
+    /// Header written at the beginning of serialized [`Map`].
+    const HEADER: Header = Header::new(MAGIC, 4, 1, S::ID);
+
     /// Returns number of bytes which `write` will write.
     pub fn write_bytes(&self) -> usize {
+        Header::SIZE + self.content_write_bytes()
+    }
+
+    /// Writes `self` to the `output`.
+    pub fn write(&self, output: &mut dyn io::Write) -> io::Result<()> {
+        Self::HEADER.write(output)?;
+        self.write_content(output)
+    }
+
+    /// Reads `self` from the `input` (`hasher` must be the same as used by written [`Map`]).
+    ///
+    /// Returns [`io::ErrorKind::InvalidData`] error if the `input` does not begin with the header of [`Map`]
+    /// serialized in the supported format with the same hasher (which is checked only for hashers with non-zero IDs).
+    pub fn read_with_hasher(input: &mut dyn io::Read, hasher: S) -> io::Result<Self> {
+        Self::HEADER.read_expected(input)?;
+        Self::read_content_with_hasher(input, hasher)
+    }
+}
+
+impl<S> Map<S> {
+    /// Returns number of bytes which `write_content` will write.
+    pub(super) fn content_write_bytes(&self) -> usize {
         AsIs::array_size(&self.values)
             + VByte::array_content_size(&self.hash_seeds)
             + VByte::size(self.third_of_values_len)
             + AsIs::size(self.bits_per_value)
     }
 
-    /// Writes `self` to the `output`.
-    pub fn write(&self, output: &mut dyn io::Write) -> io::Result<()> {
+    /// Writes `self` to the `output`, without header.
+    pub(super) fn write_content(&self, output: &mut dyn io::Write) -> io::Result<()> {
         AsIs::write_array(output, &self.values)?;
         VByte::write_all_values(output, self.hash_seeds)?;
         VByte::write(output, self.third_of_values_len)?;
         AsIs::write(output, self.bits_per_value)
     }
 
-    /// Reads `self` from the `input` (`hasher` must be the same as used by written [`Map`]).
-    pub fn read_with_hasher(input: &mut dyn io::Read, hasher: S) -> io::Result<Self> {
+    /// Reads `self`, written by `write_content`, from the `input`.
+    pub(super) fn read_content_with_hasher(input: &mut dyn io::Read, hasher: S) -> io::Result<Self> {
         let values = AsIs::read_array(input)?;
         let hash_seeds = [VByte::read(input)?, VByte::read(input)?, VByte::read(input)?];
         let third_of_values_len = VByte::read(input)?;
@@ -313,6 +341,7 @@ mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     use maplit::hashmap;
+    use crate::tests::test_get_size_against_derived;
 
     fn lsmap_1bpv_conf<BM: ValuesPreFiller>(conf: MapConf<BM>) {
         let bdzmap = Map::try_from_hashmap( hashmap!('a'=>0u8, 'b'=>0u8, 'c'=>0u8).into(), conf).unwrap();
@@ -332,6 +361,19 @@ mod tests {
         lsmap_1bpv_conf(MapConf::pattern(123u64));
     }
 
+    fn test_get_size(lsmap: Map) {
+        #[derive(GetSize)]
+        struct Derived {
+            values: Box<[u64]>,
+            #[get_size(static)] _hash_builder: BuildDefaultSeededHasher,
+            hash_seeds: [u8; 3],
+            third_of_values_len: usize,
+            bits_per_value: u8
+        }
+        test_get_size_against_derived(lsmap, |Map { values, hash_builder, hash_seeds, third_of_values_len, bits_per_value }|
+            Derived { values, _hash_builder: hash_builder, hash_seeds, third_of_values_len, bits_per_value });
+    }
+
     fn lsmap_2bpv_conf<BM: ValuesPreFiller>(conf: MapConf<BM>) {
         let lsmap: Map = Map::try_from_hashmap( hashmap!('a'=>1u8, 'b'=>2u8, 'c'=>1u8, 'd'=>3u8).into(), conf).unwrap();
         assert_eq!(lsmap.get(&'a'), 1);
@@ -339,6 +381,7 @@ mod tests {
         assert_eq!(lsmap.get(&'c'), 1);
         assert_eq!(lsmap.get(&'d'), 3);
         assert_eq!(lsmap.bits_per_value, 2);
+        test_get_size(lsmap);
     }
 
     #[test]
@@ -1,8 +1,20 @@
 #![doc = include_str!("../README.md")]
 
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicBool, AtomicI8, AtomicI16, AtomicI32, AtomicI64, AtomicIsize,
     AtomicU8, AtomicU16, AtomicU32, AtomicU64, AtomicUsize};
 
+mod report;
+pub use report::{SizeReport, PerKey};
+
+/// Derives [`GetSize`](trait@GetSize) that sums dynamic sizes of fields.
+/// Fields can be ignored by `#[get_size(skip)]` or assumed not to use dynamic memory by `#[get_size(static)]`.
+#[cfg(feature = "derive")] pub use dyn_size_of_derive::GetSize;
+
+// allows derived code, which refers to `::dyn_size_of`, to be used in tests of this crate
+#[cfg(all(test, feature = "derive"))] extern crate self as dyn_size_of;
+
 /// Provides methods to get dynamic and total size of the variable.
 pub trait GetSize {
     /// Returns approximate number of bytes occupied by dynamic (heap) part of `self`.
@@ -22,6 +34,10 @@ pub trait GetSize {
 
     /// `true` if and only if the variables of this type can use dynamic (heap) memory.
     const USES_DYN_MEM: bool = false;
+
+    /// Returns the report of the sizes of `self` and its components.
+    /// By default, the report has no children (components), and is named by the type of `self`.
+    #[inline] fn size_report(&self) -> SizeReport { SizeReport::of(self) }
 }
 
 macro_rules! impl_nodyn_getsize_for {
@@ -81,6 +97,15 @@ impl <T: GetSize> GetSize for ::std::rc::Rc<T> {
     const USES_DYN_MEM: bool = true;
 }
 
+impl <T: GetSize> GetSize for ::std::sync::Arc<T> {
+    fn size_bytes_dyn(&self) -> ::std::primitive::usize {
+        // round((size of T + size of strong and weak reference counters) / number of strong references)
+        let c = ::std::sync::Arc::strong_count(self);
+        (::std::ops::Deref::deref(self).size_bytes() + 2*::std::mem::size_of::<usize>() + c/2) / c
+    }
+    const USES_DYN_MEM: bool = true;
+}
+
 macro_rules! impl_getsize_methods_for_dyn_arr {
     ($T:ty) => (
         fn size_bytes_dyn(&self) -> ::std::primitive::usize {
@@ -98,21 +123,150 @@ impl<T: GetSize> GetSize for Box<[T]> {
     impl_getsize_methods_for_dyn_arr!(T);
 }
 
+/// Borrowed slice does not own any dynamic memory (the memory it points to is owned by someone else).
+impl<T> GetSize for &[T] {}
+
+macro_rules! impl_getsize_methods_for_vec {
+    ($T:ty) => (
+        fn size_bytes_dyn(&self) -> usize {
+            let c = ::std::mem::size_of::<$T>() * self.capacity();
+            if <$T>::USES_DYN_MEM {
+                c + self.iter().map(GetSize::size_bytes_dyn).sum::<usize>()
+            } else {
+                c
+            }
+        }
+        fn size_bytes_content_dyn(&self) -> usize {
+            let c = ::std::mem::size_of::<$T>() * self.len();
+            if <$T>::USES_DYN_MEM {
+                c + self.iter().map(GetSize::size_bytes_content_dyn).sum::<usize>()
+            } else {
+                c
+            }
+        }
+        const USES_DYN_MEM: bool = true;
+    );
+}
+
 impl<T: GetSize> GetSize for Vec<T> {
+    impl_getsize_methods_for_vec!(T);
+}
+
+impl<T: GetSize> GetSize for VecDeque<T> {
+    impl_getsize_methods_for_vec!(T);
+}
+
+impl GetSize for String {
+    #[inline] fn size_bytes_dyn(&self) -> usize { self.capacity() }
+    #[inline] fn size_bytes_content_dyn(&self) -> usize { self.len() }
+    const USES_DYN_MEM: bool = true;
+}
+
+impl<T: GetSize> GetSize for Option<T> {
     fn size_bytes_dyn(&self) -> usize {
-        let c = ::std::mem::size_of::<T>() * self.capacity();
+        self.as_ref().map_or(0, GetSize::size_bytes_dyn)
+    }
+    fn size_bytes_content_dyn(&self) -> usize {
+        self.as_ref().map_or(0, GetSize::size_bytes_content_dyn)
+    }
+    const USES_DYN_MEM: bool = T::USES_DYN_MEM;
+}
+
+/// Borrowed value does not own any dynamic memory, while owned one is measured as `B::Owned`.
+impl<B: ?Sized + ToOwned> GetSize for Cow<'_, B> where B::Owned: GetSize {
+    fn size_bytes_dyn(&self) -> usize {
+        match self { Cow::Borrowed(_) => 0, Cow::Owned(o) => o.size_bytes_dyn() }
+    }
+    fn size_bytes_content_dyn(&self) -> usize {
+        match self { Cow::Borrowed(_) => 0, Cow::Owned(o) => o.size_bytes_content_dyn() }
+    }
+    const USES_DYN_MEM: bool = B::Owned::USES_DYN_MEM;
+}
+
+/// Returns approximate number of bytes occupied by the table of standard (*SwissTable*-based) hash map or set
+/// of given `capacity` (as reported by the container) whose elements have `elem_size` bytes each.
+/// The result includes unused buckets and control bytes (one per bucket, plus a group of 16 trailing ones).
+fn hash_table_size_bytes(capacity: usize, elem_size: usize) -> usize {
+    if capacity == 0 { return 0; }  // empty tables do not allocate
+    // tables with less than 8 buckets can be fully filled, except a single bucket; larger ones up to 7/8
+    let buckets = if capacity < 8 { capacity + 1 } else { capacity / 7 * 8 };
+    buckets * (elem_size + 1) + 16
+}
+
+/// Returns approximate number of bytes occupied by the content of standard hash map or set of given `len`
+/// whose elements have `elem_size` bytes each. Each element occupies its bucket and one control byte.
+#[inline] fn hash_table_content_size_bytes(len: usize, elem_size: usize) -> usize {
+    len * (elem_size + 1)
+}
+
+impl<K: GetSize, V: GetSize, S> GetSize for HashMap<K, V, S> {
+    fn size_bytes_dyn(&self) -> usize {
+        let t = hash_table_size_bytes(self.capacity(), ::std::mem::size_of::<(K, V)>());
+        if K::USES_DYN_MEM || V::USES_DYN_MEM {
+            t + self.iter().map(|(k, v)| k.size_bytes_dyn() + v.size_bytes_dyn()).sum::<usize>()
+        } else {
+            t
+        }
+    }
+    fn size_bytes_content_dyn(&self) -> usize {
+        let t = hash_table_content_size_bytes(self.len(), ::std::mem::size_of::<(K, V)>());
+        if K::USES_DYN_MEM || V::USES_DYN_MEM {
+            t + self.iter().map(|(k, v)| k.size_bytes_content_dyn() + v.size_bytes_content_dyn()).sum::<usize>()
+        } else {
+            t
+        }
+    }
+    const USES_DYN_MEM: bool = true;
+}
+
+impl<T: GetSize, S> GetSize for HashSet<T, S> {
+    fn size_bytes_dyn(&self) -> usize {
+        let t = hash_table_size_bytes(self.capacity(), ::std::mem::size_of::<T>());
         if T::USES_DYN_MEM {
-            c + self.iter().map(GetSize::size_bytes_dyn).sum::<usize>()
+            t + self.iter().map(GetSize::size_bytes_dyn).sum::<usize>()
         } else {
-            c
+            t
         }
     }
     fn size_bytes_content_dyn(&self) -> usize {
-        let c = ::std::mem::size_of::<T>() * self.len();
+        let t = hash_table_content_size_bytes(self.len(), ::std::mem::size_of::<T>());
         if T::USES_DYN_MEM {
-            c + self.iter().map(GetSize::size_bytes_content_dyn).sum::<usize>()
+            t + self.iter().map(GetSize::size_bytes_content_dyn).sum::<usize>()
         } else {
-            c
+            t
+        }
+    }
+    const USES_DYN_MEM: bool = true;
+}
+
+/// Returns approximate number of bytes occupied by the nodes of standard B-tree map with `len` elements
+/// whose keys and values have `key_size` and `value_size` bytes, respectively.
+///
+/// Each node has room for 11 elements, and nodes are assumed to be filled in about 3/4 (8 elements),
+/// which is between the minimum fill of 5 elements and the fill of maps built from sorted sequences.
+/// Internal nodes, which are about 1/9 of all nodes (as each has about 9 children), store also 12 child pointers.
+fn btree_size_bytes(len: usize, key_size: usize, value_size: usize) -> usize {
+    if len == 0 { return 0; }   // empty maps do not allocate
+    let leaf_size = ::std::mem::size_of::<usize>() + 2 * ::std::mem::size_of::<u16>() + 11 * (key_size + value_size);
+    let nodes = len.div_ceil(8);
+    nodes * leaf_size + nodes / 9 * 12 * ::std::mem::size_of::<usize>()
+}
+
+impl<K: GetSize, V: GetSize> GetSize for BTreeMap<K, V> {
+    fn size_bytes_dyn(&self) -> usize {
+        let t = btree_size_bytes(self.len(), ::std::mem::size_of::<K>(), ::std::mem::size_of::<V>());
+        if K::USES_DYN_MEM || V::USES_DYN_MEM {
+            t + self.iter().map(|(k, v)| k.size_bytes_dyn() + v.size_bytes_dyn()).sum::<usize>()
+        } else {
+            t
+        }
+    }
+    fn size_bytes_content_dyn(&self) -> usize {
+        let t = self.len() * (::std::mem::size_of::<K>() + ::std::mem::size_of::<V>());
+        if K::USES_DYN_MEM || V::USES_DYN_MEM {
+            t + self.iter().map(|(k, v)| k.size_bytes_content_dyn() + v.size_bytes_content_dyn()).sum::<usize>()
+        } else {
+            t
         }
     }
     const USES_DYN_MEM: bool = true;
@@ -202,4 +356,87 @@ mod tests {
         assert_eq!(Box::new(1u32).size_bytes_dyn(), 4);
         assert_eq!(Box::new([1u32, 2u32]).size_bytes_dyn(), 2*4);
     }
+
+    #[test]
+    fn test_string_option_cow() {
+        let mut s = String::with_capacity(10);
+        s.push_str("abc");
+        assert_eq!(s.size_bytes_dyn(), 10);
+        assert_eq!(s.size_bytes_content_dyn(), 3);
+        assert_eq!(Some(s.clone()).size_bytes_content_dyn(), 3);
+        assert_eq!(None::<String>.size_bytes_dyn(), 0);
+        const { assert!(!Option::<u32>::USES_DYN_MEM) };
+        assert_eq!(Cow::Borrowed("abc").size_bytes_dyn(), 0);
+        assert_eq!(Cow::<str>::Owned(s).size_bytes_dyn(), 10);
+        assert_eq!(Cow::<[u32]>::Owned(vec![1, 2]).size_bytes_content_dyn(), 2*4);
+    }
+
+    #[test]
+    fn test_arc_vecdeque() {
+        let a = std::sync::Arc::new(1u64);
+        assert_eq!(a.size_bytes_dyn(), 8 + 2*std::mem::size_of::<usize>());
+        let b = a.clone();
+        assert_eq!(b.size_bytes_dyn(), (8 + 2*std::mem::size_of::<usize>()) / 2);
+        let mut d = VecDeque::with_capacity(8);
+        d.extend([1u32, 2, 3]);
+        assert_eq!(d.size_bytes_dyn(), d.capacity()*4);
+        assert_eq!(d.size_bytes_content_dyn(), 3*4);
+    }
+
+    #[test]
+    fn test_hash_containers() {
+        assert_eq!(HashMap::<u32, u64>::new().size_bytes_dyn(), 0);
+        let m: HashMap<u32, u64> = (0..100).map(|k| (k, k as u64)).collect();
+        assert!(m.size_bytes_dyn() >= 100 * (16 + 1) + 16);
+        assert!(m.size_bytes_dyn() <= 2 * 100 * (16 + 1) + 16);
+        assert_eq!(m.size_bytes_content_dyn(), 100 * (16 + 1));
+        let s: HashSet<String> = ["a".to_owned(), "bc".to_owned()].into_iter().collect();
+        assert_eq!(s.size_bytes_content_dyn(), 2 * (std::mem::size_of::<String>() + 1) + 3);
+        assert!(s.size_bytes_dyn() >= s.size_bytes_content_dyn());
+    }
+
+    #[test]
+    fn test_btree_map() {
+        assert_eq!(BTreeMap::<u32, u32>::new().size_bytes_dyn(), 0);
+        let m: BTreeMap<u32, u32> = (0..100).map(|k| (k, k)).collect();
+        assert_eq!(m.size_bytes_content_dyn(), 100 * 8);
+        assert!(m.size_bytes_dyn() > 100 * 8);
+        assert!(m.size_bytes_dyn() < 3 * 100 * 8);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive() {
+        #[derive(GetSize)]
+        struct NoHeapMem(u32, #[get_size(static)] std::marker::PhantomData<String>);
+        const { assert!(!NoHeapMem::USES_DYN_MEM) };
+        assert_eq!(NoHeapMem(1, std::marker::PhantomData).size_bytes_dyn(), 0);
+
+        #[derive(GetSize)]
+        struct WithHeapMem<T> {
+            a: Vec<T>,
+            b: Box<[u8]>,
+            #[get_size(skip)] _c: std::rc::Rc<u64>,
+            d: u32
+        }
+        let mut v = Vec::with_capacity(4);
+        v.push(1u16);
+        let s = WithHeapMem { a: v, b: vec![1, 2, 3].into_boxed_slice(), _c: std::rc::Rc::new(1), d: 5 };
+        const { assert!(WithHeapMem::<u16>::USES_DYN_MEM) };
+        assert_eq!(s.size_bytes_dyn(), 4*2 + 3);
+        assert_eq!(s.size_bytes_content_dyn(), 2 + 3);
+        assert_eq!(s.d, 5);
+        let report = s.size_report();
+        assert_eq!(report.name, "WithHeapMem<u16>");
+        assert_eq!(report.children.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["a", "b", "d"]);
+        assert_eq!(report.children[1].bytes, s.b.size_bytes());
+
+        #[derive(GetSize)]
+        enum Either { Left(Vec<u32>), Right { a: u8, b: Box<u64> }, None }
+        const { assert!(Either::USES_DYN_MEM) };
+        assert_eq!(Either::Left(vec![1, 2]).size_bytes_dyn(), 2*4);
+        assert_eq!(Either::Right { a: 1, b: Box::new(2) }.size_bytes_dyn(), 8);
+        assert_eq!(Either::None.size_bytes_dyn(), 0);
+        assert_eq!(Either::Left(vec![1]).size_report().children[0].name, "0");
+    }
 }
\ No newline at end of file
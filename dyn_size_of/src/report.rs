@@ -0,0 +1,133 @@
+use std::fmt;
+
+use crate::GetSize;
+
+/// Named tree of the sizes of the components of a value (see [`GetSize::size_report`]).
+///
+/// Each node reports the total number of bytes occupied by some component (including its heap memory),
+/// and its children report the sizes of the (selected) sub-components.
+/// The report can be printed as an indented tree by its [`Display`](fmt::Display) implementation,
+/// or by [`SizeReport::per_key`] to include also the number of bits per key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SizeReport {
+    /// Name of the component.
+    pub name: String,
+    /// Number of bytes occupied by the component.
+    pub bytes: usize,
+    /// Reports of sub-components.
+    pub children: Vec<SizeReport>
+}
+
+/// Returns the name of type `T` with all module paths removed (e.g. `Vec<u32>` instead of `alloc::vec::Vec<u32>`).
+fn short_type_name<T: ?Sized>() -> String {
+    let full = std::any::type_name::<T>();
+    let mut result = String::with_capacity(full.len());
+    let mut segment_begin = 0;  // the index in result of the first character of current path segment
+    let mut chars = full.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            result.truncate(segment_begin);
+        } else {
+            result.push(c);
+            if !c.is_alphanumeric() && c != '_' { segment_begin = result.len(); }
+        }
+    }
+    result
+}
+
+impl SizeReport {
+    /// Constructs report without children.
+    pub fn new<N: Into<String>>(name: N, bytes: usize) -> Self {
+        Self { name: name.into(), bytes, children: Vec::new() }
+    }
+
+    /// Constructs report without children, for the `value` of type `T`, named by the (short) name of `T`.
+    pub fn of<T: GetSize + ?Sized>(value: &T) -> Self {
+        Self::new(short_type_name::<T>(), value.size_bytes())
+    }
+
+    /// Returns `self` with the name changed to `name`.
+    pub fn named<N: Into<String>>(mut self, name: N) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Returns `self` with the `child` report appended to the children.
+    pub fn child(mut self, child: SizeReport) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Returns `self` with the report of the `value` (named `name`) appended to the children.
+    #[inline] pub fn field<N: Into<String>, T: GetSize + ?Sized>(self, name: N, value: &T) -> Self {
+        self.child(value.size_report().named(name))
+    }
+
+    /// Returns the number of bits per key occupied by the component, for a structure that stores `keys` keys.
+    #[inline] pub fn bits_per_key(&self, keys: usize) -> f64 {
+        (self.bytes * 8) as f64 / keys as f64
+    }
+
+    /// Returns the object that displays `self` (see [`SizeReport`]) with the numbers of bits per key
+    /// for a structure that stores `keys` keys.
+    #[inline] pub fn per_key(&self, keys: usize) -> PerKey<'_> {
+        PerKey { report: self, keys }
+    }
+
+    /// Writes the tree rooted in `self` to `f`, indenting it by `depth` levels.
+    fn fmt_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize, total: usize, keys: Option<usize>) -> fmt::Result {
+        write!(f, "{:indent$}{}: {} bytes", "", self.name, self.bytes, indent = 2 * depth)?;
+        if depth != 0 && total != 0 {
+            write!(f, " ({:.1}%)", self.bytes as f64 * 100.0 / total as f64)?;
+        }
+        if let Some(keys) = keys {
+            write!(f, ", {:.3} bits/key", self.bits_per_key(keys))?;
+        }
+        writeln!(f)?;
+        self.children.iter().try_for_each(|c| c.fmt_tree(f, depth + 1, total, keys))
+    }
+}
+
+impl fmt::Display for SizeReport {
+    /// Writes the report as the tree, one component per line, with the children indented
+    /// and with the sizes of sub-components given also as the percentages of the size of the root.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_tree(f, 0, self.bytes, None)
+    }
+}
+
+/// Displays [`SizeReport`] with the numbers of bits per key (see [`SizeReport::per_key`]).
+pub struct PerKey<'r> {
+    report: &'r SizeReport,
+    keys: usize
+}
+
+impl fmt::Display for PerKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.report.fmt_tree(f, 0, self.report.bytes, Some(self.keys))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(short_type_name::<u32>(), "u32");
+        assert_eq!(short_type_name::<Vec<String>>(), "Vec<String>");
+        assert_eq!(short_type_name::<(Box<[u8]>, std::collections::HashMap<u8, u8>)>(), "(Box<[u8]>, HashMap<u8, u8>)");
+    }
+
+    #[test]
+    fn test_report() {
+        let v = vec![1u32, 2, 3, 4];
+        let report = SizeReport::new("pair", 100).field("v", &v).child(SizeReport::new("other", 25));
+        assert_eq!(report.children[0], SizeReport::new("v", v.size_bytes()));
+        assert_eq!(report.bits_per_key(100), 8.0);
+        assert_eq!(format!("{}", report),
+            format!("pair: 100 bytes\n  v: {} bytes ({:.1}%)\n  other: 25 bytes (25.0%)\n", v.size_bytes(), v.size_bytes() as f64));
+        assert_eq!(format!("{}", SizeReport::new("a", 2).per_key(4)), "a: 2 bytes, 4.000 bits/key\n");
+    }
+}
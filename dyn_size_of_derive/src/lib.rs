@@ -0,0 +1,141 @@
+#![doc = include_str!("../README.md")]
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident, Member, Type};
+
+/// Derives `dyn_size_of::GetSize` for a struct or an enum.
+///
+/// The dynamic size of the value is the sum of dynamic sizes of its fields (of the fields of the current variant, for enums),
+/// and the type uses dynamic memory if the type of any of its fields does.
+///
+/// The fields marked by:
+/// - `#[get_size(skip)]` are ignored, which is useful for the fields that refer to the memory owned by someone else,
+/// - `#[get_size(static)]` are assumed not to use dynamic memory, which is useful for the fields
+///   whose types do not implement `GetSize` (like hashers or `PhantomData`).
+///
+/// The types of the other fields must implement `GetSize`.
+/// They are also reported (under their names or indices) as the children of the report returned by `size_report`.
+#[proc_macro_derive(GetSize, attributes(get_size))]
+pub fn derive_get_size(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Returns `true` if the `field` is marked by `#[get_size(skip)]` or `#[get_size(static)]`.
+fn is_ignored(field: &syn::Field) -> syn::Result<bool> {
+    let mut ignored = false;
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("get_size")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") || meta.path.is_ident("static") {
+                ignored = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported get_size attribute, expected `skip` or `static`"))
+            }
+        })?;
+    }
+    Ok(ignored)
+}
+
+/// Returns `true` if `tokens` contain any of the `params`.
+fn mentions_any(tokens: TokenStream, params: &[&Ident]) -> bool {
+    tokens.into_iter().any(|t| match t {
+        TokenTree::Ident(ident) => params.contains(&&ident),
+        TokenTree::Group(group) => mentions_any(group.stream(), params),
+        _ => false
+    })
+}
+
+/// Match arm of one struct or variant, with the (counted) fields bound to variables.
+struct Arm {
+    pattern: TokenStream,
+    bindings: Vec<Ident>,
+    names: Vec<String>
+}
+
+/// Returns the match arm for the `fields` of the struct or variant at `path`, and appends the types of counted fields to `types`.
+fn arm<'a>(path: TokenStream, fields: &'a Fields, types: &mut Vec<&'a Type>) -> syn::Result<Arm> {
+    let mut members = Vec::new();
+    let mut bindings = Vec::new();
+    let mut names = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        if is_ignored(field)? { continue; }
+        members.push(match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(i.into())
+        });
+        names.push(field.ident.as_ref().map_or_else(|| i.to_string(), Ident::to_string));
+        bindings.push(format_ident!("__field{}", i));
+        types.push(&field.ty);
+    }
+    Ok(Arm { pattern: quote!(#path { #(#members: #bindings,)* .. }), bindings, names })
+}
+
+/// Returns the sum of `method` calls for `bindings`.
+fn sum(method: &Ident, bindings: &[Ident]) -> TokenStream {
+    if bindings.is_empty() {
+        quote!(0)
+    } else {
+        quote!(#(::dyn_size_of::GetSize::#method(#bindings))+*)
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let mut types = Vec::new();
+    let arms = match &input.data {
+        Data::Struct(data) => vec![arm(quote!(Self), &data.fields, &mut types)?],
+        Data::Enum(data) => data.variants.iter().map(|v| {
+            let name = &v.ident;
+            arm(quote!(Self::#name), &v.fields, &mut types)
+        }).collect::<syn::Result<_>>()?,
+        Data::Union(data) => return Err(syn::Error::new(data.union_token.span, "GetSize cannot be derived for unions"))
+    };
+
+    let params: Vec<_> = input.generics.type_params().map(|p| &p.ident).collect();
+    let mut generics = input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for ty in &types {
+        if mentions_any(quote!(#ty), &params) {
+            where_clause.predicates.push(syn::parse_quote!(#ty: ::dyn_size_of::GetSize));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let name = &input.ident;
+
+    if arms.is_empty() {  // enum without variants
+        return Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics ::dyn_size_of::GetSize for #name #ty_generics #where_clause {
+                fn size_bytes_dyn(&self) -> ::std::primitive::usize { match *self {} }
+            }
+        });
+    }
+
+    let patterns: Vec<_> = arms.iter().map(|a| &a.pattern).collect();
+    let dyn_sums = arms.iter().map(|a| sum(&format_ident!("size_bytes_dyn"), &a.bindings));
+    let content_sums = arms.iter().map(|a| sum(&format_ident!("size_bytes_content_dyn"), &a.bindings));
+    let reports = arms.iter().map(|Arm { bindings, names, .. }|
+        quote!(::dyn_size_of::SizeReport::of(self) #(.field(#names, #bindings))*));
+    let uses_dyn_mem = if types.is_empty() {
+        quote!(false)
+    } else {
+        quote!(#(<#types as ::dyn_size_of::GetSize>::USES_DYN_MEM)||*)
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::dyn_size_of::GetSize for #name #ty_generics #where_clause {
+            fn size_bytes_dyn(&self) -> ::std::primitive::usize {
+                match self { #(#patterns => #dyn_sums,)* }
+            }
+            fn size_bytes_content_dyn(&self) -> ::std::primitive::usize {
+                match self { #(#patterns => #content_sums,)* }
+            }
+            const USES_DYN_MEM: bool = #uses_dyn_mem;
+            fn size_report(&self) -> ::dyn_size_of::SizeReport {
+                match self { #(#patterns => #reports,)* }
+            }
+        }
+    })
+}
@@ -3,6 +3,7 @@
 use std::mem;
 use std::ops::{AddAssign, SubAssign};
 use std::borrow::Borrow;
+use std::iter::Sum;
 
 /// Accumulator that represents the exact sum of `f64` values and
 /// allows additional `f64` values to be added without loss of precision.
@@ -122,6 +123,65 @@ impl FSum {
         self
     }
 
+    /// Constructs accumulator with all `values`, summing them in parallel.
+    ///
+    /// The result is exactly the same as the one of [`FSum::with_all`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fsum::FSum;
+    ///
+    /// let values = vec![0.1; 1000];
+    /// assert_eq!(FSum::with_all_par(&values[..]).value(), 100.0);
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn with_all_par<Bf64, InIter>(values: InIter) -> FSum
+        where Bf64: Borrow<f64>, InIter: rayon::iter::IntoParallelIterator<Item=Bf64>
+    {
+        use rayon::prelude::*;
+        values.into_par_iter()
+            .fold(Self::new, |mut s, x| { s.add(*x.borrow()); s })
+            .reduce(Self::new, |a, b| a + b)
+    }
+
+    /// Increases the sum by all `values`, summing them in parallel. Returns `self`.
+    ///
+    /// The result is exactly the same as the one of [`FSum::add_all`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fsum::FSum;
+    ///
+    /// assert_eq!(FSum::with_value(1e100).add_all_par(&[1.0, -1e100]).value(), 1.0);
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn add_all_par<Bf64, InIter>(&mut self, values: InIter) -> &mut FSum
+        where Bf64: Borrow<f64>, InIter: rayon::iter::IntoParallelIterator<Item=Bf64>
+    {
+        let sum = Self::with_all_par(values);
+        self.add_fsum(&sum)
+    }
+
+    /// Increases the sum by the (exact) sum represented by `other`. Returns `self`.
+    ///
+    /// The result is the same as if all values added to `other` were added to `self`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fsum::FSum;
+    ///
+    /// let other = FSum::with_all(&[1.0, -1e100]);
+    /// assert_eq!(FSum::with_value(1e100).add_fsum(&other).value(), 1.0);
+    /// ```
+    pub fn add_fsum(&mut self, other: &FSum) -> &mut FSum {
+        // partials of other are exact, so adding them one by one keeps self exact
+        for x in &other.partials { self.add(*x); }
+        self
+    }
+
     /// Returns the current value of the sum.
     ///
     /// The complexities are:
@@ -193,6 +253,50 @@ impl AddAssign<f64> for FSum {
     #[inline] fn add_assign(&mut self, other: f64) { self.add(other); }
 }
 
+impl AddAssign<&FSum> for FSum {
+    #[inline] fn add_assign(&mut self, other: &FSum) { self.add_fsum(other); }
+}
+
+impl AddAssign<FSum> for FSum {
+    #[inline] fn add_assign(&mut self, other: FSum) { self.add_fsum(&other); }
+}
+
+// `Add` is not imported, as its `add` method would shadow `FSum::add` for the values of `FSum`.
+impl std::ops::Add<&FSum> for FSum {
+    type Output = FSum;
+    #[inline] fn add(mut self, other: &FSum) -> FSum { self.add_fsum(other); self }
+}
+
+impl std::ops::Add<FSum> for FSum {
+    type Output = FSum;
+    /// Returns exact sum of `self` and `other`, reusing the one with more partials.
+    fn add(mut self, mut other: FSum) -> FSum {
+        if self.partials.len() < other.partials.len() { mem::swap(&mut self, &mut other); }
+        self.add_fsum(&other);
+        self
+    }
+}
+
+impl Sum<f64> for FSum {
+    #[inline] fn sum<I: Iterator<Item = f64>>(iter: I) -> Self { Self::with_all(iter) }
+}
+
+impl<'a> Sum<&'a f64> for FSum {
+    #[inline] fn sum<I: Iterator<Item = &'a f64>>(iter: I) -> Self { Self::with_all(iter) }
+}
+
+impl Sum<FSum> for FSum {
+    #[inline] fn sum<I: Iterator<Item = FSum>>(iter: I) -> Self { iter.fold(Self::new(), |a, b| a + b) }
+}
+
+impl<'a> Sum<&'a FSum> for FSum {
+    fn sum<I: Iterator<Item = &'a FSum>>(iter: I) -> Self {
+        let mut result = Self::new();
+        for s in iter { result.add_fsum(s); }
+        result
+    }
+}
+
 impl SubAssign<f64> for FSum {
     #[inline] fn sub_assign(&mut self, other: f64) { self.add(- other); }
 }
@@ -246,4 +350,53 @@ mod tests {
         assert_eq!(s.value(), 5.0);
         assert_eq!(5.0, s.into());
     }
+
+    /// Returns `n` pseudo-random values of very different magnitudes and signs.
+    fn values(n: usize) -> Vec<f64> {
+        let mut seed = 1234567u64;
+        (0..n).map(|_| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let mantissa = (seed >> 11) as f64 / (1u64 << 53) as f64;
+            let sign = if seed & 1 == 0 { 1.0 } else { -1.0 };
+            sign * mantissa * 2f64.powi((seed >> 1 & 127) as i32 - 64)
+        }).collect()
+    }
+
+    #[test]
+    fn fsum_merge() {
+        let v = values(1000);
+        let expected = FSum::with_all(&v).value();
+        for split in [0, 1, 7, 500, 999, 1000] {
+            let (a, b) = v.split_at(split);
+            assert_eq!((FSum::with_all(a) + FSum::with_all(b)).value(), expected);
+            assert_eq!((FSum::with_all(b) + &FSum::with_all(a)).value(), expected);
+            let mut s = FSum::with_all(a);
+            s += FSum::with_all(b);
+            assert_eq!(s.value(), expected);
+        }
+        assert_eq!((FSum::with_all([1e100, 1.0]) + FSum::with_value(-1e100)).value(), 1.0);
+    }
+
+    #[test]
+    fn fsum_sum() {
+        let v = values(1000);
+        let expected = FSum::with_all(&v).value();
+        assert_eq!(v.iter().sum::<FSum>().value(), expected);
+        assert_eq!(v.iter().copied().sum::<FSum>().value(), expected);
+        assert_eq!(v.chunks(33).map(FSum::with_all).sum::<FSum>().value(), expected);
+        let chunks: Vec<_> = v.chunks(10).map(FSum::with_all).collect();
+        assert_eq!(chunks.iter().sum::<FSum>().value(), expected);
+        assert_eq!(std::iter::empty::<f64>().sum::<FSum>().value(), 0.0);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn fsum_par() {
+        let v = values(100_000);
+        let expected = FSum::with_all(&v).value();
+        assert_eq!(FSum::with_all_par(&v[..]).value(), expected);
+        assert_eq!(FSum::with_all_par(v.clone()).value(), expected);
+        assert_eq!(FSum::with_value(1.0).add_all_par(&v[..]).value(), FSum::with_value(1.0).add_all(&v).value());
+        assert_eq!(FSum::with_all_par([1e100, 1.0, -1e100]).value(), 1.0);
+    }
 }
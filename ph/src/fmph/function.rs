@@ -1,15 +1,15 @@
 use std::hash::Hash;
-use binout::{AsIs, Serializer, VByte};
-use bitm::{BitAccess, BitArrayWithRank, ceiling_div};
+use binout::{AsIs, Header, Serializer, VByte};
+use bitm::{BitAccess, ceiling_div};
 
-use crate::utils::ArrayWithRank;
+use crate::utils::{ArrayWithRank, build_array_with_rank, build_array_with_rank_par};
 use crate::{BuildDefaultSeededHasher, BuildSeededHasher, stats, utils};
 
 use std::io;
 use std::sync::atomic::{AtomicU64};
 use std::sync::atomic::Ordering::Relaxed;
 use rayon::prelude::*;
-use dyn_size_of::GetSize;
+use dyn_size_of::{GetSize, SizeReport};
 
 use crate::fmph::keyset::{KeySet, SliceMutSource, SliceSourceWithRefs};
 
@@ -367,6 +367,9 @@ impl<S: BuildSeededHasher> GetSize for Function<S> {
     fn size_bytes_dyn(&self) -> usize { self.array.size_bytes_dyn() + self.level_sizes.size_bytes_dyn() }
     fn size_bytes_content_dyn(&self) -> usize { self.array.size_bytes_content_dyn() + self.level_sizes.size_bytes_content_dyn() }
     const USES_DYN_MEM: bool = true;
+    fn size_report(&self) -> SizeReport {
+        SizeReport::of(self).field("array", &self.array).field("level_sizes", &self.level_sizes)
+    }
 }
 
 impl<S: BuildSeededHasher> Function<S> {
@@ -387,7 +390,7 @@ impl<S: BuildSeededHasher> Function<S> {
         loop {
             let level_size = (*self.level_sizes.get(level_nr as usize)? as usize) << 6;
             let i = array_begin_index + self.index(key, level_nr, level_size);
-            if self.array.content.get_bit(i) {
+            if self.array.get_bit(i) {
                 access_stats.found_on_level(level_nr);
                 return Some(self.array.rank(i) as u64);
             }
@@ -404,25 +407,33 @@ impl<S: BuildSeededHasher> Function<S> {
         self.get_stats(key, &mut ())
     }
 
+    /// Header written at the beginning of serialized [Function].
+    const HEADER: Header = Header::new(utils::MAGIC, 1, 1, S::ID);
+
     /// Returns number of bytes which `write` will write.
     pub fn write_bytes(&self) -> usize {
-        VByte::array_size(&self.level_sizes) + AsIs::array_content_size(&self.array.content)
+        Header::SIZE + VByte::array_size(&self.level_sizes) + self.array.content_words().len() * std::mem::size_of::<u64>()
     }
 
     /// Writes `self` to the `output`.
     pub fn write(&self, output: &mut dyn io::Write) -> io::Result<()>
     {
+        Self::HEADER.write(output)?;
         VByte::write_array(output, &self.level_sizes)?;
-        AsIs::write_all(output, self.array.content.iter())
+        AsIs::write_all_values(output, self.array.content_words())
     }
 
     /// Reads `Self` from the `input`. Hasher must be the same as the one used to write.
+    ///
+    /// Returns [`io::ErrorKind::InvalidData`] error if the `input` does not begin with the header of [Function]
+    /// serialized in the supported format with the same hasher (which is checked only for hashers with non-zero IDs).
     pub fn read_with_hasher(input: &mut dyn io::Read, hasher: S) -> io::Result<Self>
     {
+        Self::HEADER.read_expected(input)?;
         let level_sizes = VByte::read_array(input)?;
         let array_content_len = level_sizes.iter().map(|v|*v as usize).sum::<usize>();
         let array_content = AsIs::read_n(input, array_content_len)?;
-        let (array_with_rank, _) = ArrayWithRank::build(array_content);
+        let (array_with_rank, _) = build_array_with_rank_par(array_content);
         Ok(Self { array: array_with_rank, level_sizes, hash_builder: hasher })
     }
 
@@ -443,7 +454,7 @@ impl<S: BuildSeededHasher + Sync> Function<S> {
         drop(keys);
         stats.end();
         let level_sizes = builder.arrays.iter().map(|l| l.len() as u64).collect();
-        let (array, _)  = ArrayWithRank::build(builder.arrays.concat().into_boxed_slice());
+        let (array, _)  = build_array_with_rank(builder.arrays.concat().into_boxed_slice(), builder.use_multiple_threads);
         Self {
             array,
             level_sizes,
@@ -541,19 +552,46 @@ pub(crate) mod tests {
         }
     }
 
+    /// Checks the hand-written [`GetSize`] implementation of `value` against the derived one of the structure
+    /// (with the same fields as `value`) returned by `into_derived`.
+    pub fn test_get_size_against_derived<T: GetSize, D: GetSize>(value: T, into_derived: impl FnOnce(T) -> D) {
+        let (size_dyn, size_content_dyn) = (value.size_bytes_dyn(), value.size_bytes_content_dyn());
+        let derived = into_derived(value);
+        assert_eq!(derived.size_bytes_dyn(), size_dyn);
+        assert_eq!(derived.size_bytes_content_dyn(), size_content_dyn);
+        assert_eq!(D::USES_DYN_MEM, T::USES_DYN_MEM);
+    }
+
     fn test_read_write(h: &Function) {
         let mut buff = Vec::new();
         h.write(&mut buff).unwrap();
         assert_eq!(buff.len(), h.write_bytes());
         let read = Function::read(&mut &buff[..]).unwrap();
         assert_eq!(h.level_sizes.len(), read.level_sizes.len());
-        assert_eq!(h.array.content, read.array.content);
+        assert_eq!(h.array.content_words().collect::<Vec<_>>(), read.array.content_words().collect::<Vec<_>>());
+    }
+
+    /// Checks the size report and the hand-written [`GetSize`] implementation of `h`.
+    fn test_get_size(h: Function) {
+        #[derive(GetSize)]
+        struct Derived {
+            array: ArrayWithRank,
+            level_sizes: Box<[u64]>,
+            #[get_size(static)] _hash_builder: BuildDefaultSeededHasher
+        }
+        let report = h.size_report();
+        assert_eq!(report.bytes, h.size_bytes());
+        assert_eq!(report.children[0], h.array.size_report().named("array"));
+        assert_eq!(report.children[1].bytes, h.level_sizes.size_bytes());
+        test_get_size_against_derived(h, |Function { array, level_sizes, hash_builder }|
+            Derived { array, level_sizes, _hash_builder: hash_builder });
     }
 
     fn test_with_input<K: Hash + Clone + Display + Sync>(to_hash: &[K]) {
         let h = Function::from_slice_with_conf(to_hash, BuildConf::mt(false));
         test_mphf(to_hash, |key| h.get(key).map(|i| i as usize));
         test_read_write(&h);
+        test_get_size(h);
     }
 
     #[test]
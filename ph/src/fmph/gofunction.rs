@@ -1,8 +1,8 @@
 use std::hash::Hash;
-use binout::{VByte, Serializer, AsIs};
-use bitm::{BitAccess, BitArrayWithRank, ceiling_div};
+use binout::{VByte, Serializer, AsIs, Header};
+use bitm::{BitAccess, ceiling_div};
 
-use crate::utils::{ArrayWithRank, read_bits};
+use crate::utils::{ArrayWithRank, build_array_with_rank, build_array_with_rank_par, read_bits, MAGIC};
 use crate::{BuildDefaultSeededHasher, BuildSeededHasher, stats};
 
 use super::Bits8;
@@ -10,7 +10,7 @@ use super::function::{from_mut_slice, get_mut_slice};
 use super::goindexing::{GroupSize, SeedSize, TwoToPowerBitsStatic};
 use std::io;
 use std::sync::atomic::AtomicU64;
-use dyn_size_of::GetSize;
+use dyn_size_of::{GetSize, SizeReport};
 use crate::fmph::function::{fphash_add_bit, fphash_remove_collided, fphash_sync_add_bit};
 use crate::fmph::goindexing::group_nr;
 
@@ -418,6 +418,12 @@ impl<GS: GroupSize, SS: SeedSize, S: BuildSeededHasher> GetSize for GOFunction<G
     }
 
     const USES_DYN_MEM: bool = true;
+
+    fn size_report(&self) -> SizeReport {
+        SizeReport::of(self).field("array", &self.array)
+            .field("group_seeds", &self.group_seeds)
+            .field("level_sizes", &self.level_sizes)
+    }
 }
 
 impl<GS: GroupSize, SS: SeedSize, S: BuildSeededHasher> GOFunction<GS, SS, S> {
@@ -438,7 +444,7 @@ impl<GS: GroupSize, SS: SeedSize, S: BuildSeededHasher> GOFunction<GS, SS, S> {
             let group = groups_before + group_nr(hash, level_size_groups);
             let seed = self.conf.bits_per_seed.get_seed(&self.group_seeds, group as usize);
             let bit_index = self.conf.bits_per_group.bit_index_for_seed(hash, seed, group);
-            if self.array.content.get_bit(bit_index) {
+            if self.array.get_bit(bit_index) {
                 access_stats.found_on_level(level_nr);
                 return Some(self.array.rank(bit_index) as u64);
             }
@@ -455,32 +461,41 @@ impl<GS: GroupSize, SS: SeedSize, S: BuildSeededHasher> GOFunction<GS, SS, S> {
         self.get_stats(key, &mut ())
     }
 
+    /// Header written at the beginning of serialized [GOFunction].
+    const HEADER: Header = Header::new(MAGIC, 2, 1, S::ID);
+
     /// Returns number of bytes which `write` will write.
     pub fn write_bytes(&self) -> usize {
-        self.conf.bits_per_group.write_size_bytes()
+        Header::SIZE
+            + self.conf.bits_per_group.write_size_bytes()
             + VByte::array_size(&self.level_sizes)
-            + AsIs::array_content_size(&self.array.content)
+            + self.array.content_words().len() * std::mem::size_of::<u64>()
             + std::mem::size_of::<u8>() + self.group_seeds.size_bytes_content_dyn()
     }
 
     /// Writes `self` to the `output`.
     pub fn write(&self, output: &mut dyn io::Write) -> io::Result<()>
     {
+        Self::HEADER.write(output)?;
         self.conf.bits_per_group.write(output)?;
         VByte::write_array(output, &self.level_sizes)?;
-        AsIs::write_all(output, self.array.content.iter())?;
+        AsIs::write_all_values(output, self.array.content_words())?;
         self.conf.bits_per_seed.write_seed_vec(output, &self.group_seeds)
     }
 
     /// Reads `Self` from the `input`. Hash builder must be the same as the one used to write.
+    ///
+    /// Returns [`io::ErrorKind::InvalidData`] error if the `input` does not begin with the header of [GOFunction]
+    /// serialized in the supported format with the same hasher (which is checked only for hashers with non-zero IDs).
     pub fn read_with_hasher(input: &mut dyn io::Read, hash_builder: S) -> io::Result<Self>
     {
+        Self::HEADER.read_expected(input)?;
         let bits_per_group = GS::read(input)?;
         let level_size = VByte::read_array(input)?;
         let number_of_groups = level_size.iter().map(|v|*v as usize).sum::<usize>();
 
         let array_content = read_bits(input, bits_per_group * number_of_groups)?;
-        let (array_with_rank, _) = ArrayWithRank::build(array_content);
+        let (array_with_rank, _) = build_array_with_rank_par(array_content);
 
         let (bits_per_group_seed, group_seeds) = SS::read_seed_vec(input, number_of_groups)?;
 
@@ -519,7 +534,7 @@ impl<GS: GroupSize + Sync, SS: SeedSize, S: BuildSeededHasher + Sync> GOFunction
         }
         drop(keys);
         stats.end();
-        let (array, _)  = ArrayWithRank::build(conf.arrays.concat().into_boxed_slice());
+        let (array, _)  = build_array_with_rank(conf.arrays.concat().into_boxed_slice(), conf.use_multiple_threads);
         let group_seeds_concatenated = conf.goconf.bits_per_seed.concatenate_seed_vecs(&conf.level_sizes, conf.group_seeds);
         Self {
             array,
@@ -611,7 +626,7 @@ impl<K: Hash + Sync + Send> From<Vec<K>> for GOFunction {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::fmph::function::tests::test_mphf;
+    use crate::fmph::function::tests::{test_mphf, test_get_size_against_derived};
     use crate::fmph::TwoToPowerBits;
     use std::fmt::{Debug, Display};
     use crate::fmph::Bits;
@@ -624,13 +639,36 @@ mod tests {
         assert_eq!(buff.len(), h.write_bytes());
         let read = GOFunction::<GS, SS>::read(&mut &buff[..]).unwrap();
         assert_eq!(h.level_sizes, read.level_sizes);
-        assert_eq!(h.array.content, read.array.content);
+        assert_eq!(h.array.content_words().collect::<Vec<_>>(), read.array.content_words().collect::<Vec<_>>());
         assert_eq!(h.group_seeds, read.group_seeds);
     }
 
+    #[test]
+    fn test_read_other_structure() {
+        let mut buff = Vec::new();
+        crate::fmph::Function::from(&[1, 2, 3][..]).write(&mut buff).unwrap();
+        let read = GOFunction::<TwoToPowerBitsStatic<4>, TwoToPowerBitsStatic<2>>::read(&mut &buff[..]);
+        assert_eq!(read.err().map(|e| e.kind()), Some(io::ErrorKind::InvalidData));
+        assert!(crate::fmph::Function::read(&mut &buff[..]).is_ok());
+        buff[0] = b'X';
+        assert_eq!(crate::fmph::Function::read(&mut &buff[..]).err().map(|e| e.kind()), Some(io::ErrorKind::InvalidData));
+    }
+
+    fn test_get_size<GS: GroupSize, SS: SeedSize>(h: GOFunction<GS, SS>) {
+        #[derive(GetSize)]
+        struct Derived<GS: GroupSize, SS: SeedSize> {
+            array: ArrayWithRank,
+            group_seeds: Box<[SS::VecElement]>,
+            level_sizes: Box<[u64]>,
+            #[get_size(static)] _conf: GOConf<GS, SS>
+        }
+        test_get_size_against_derived(h, |GOFunction { array, group_seeds, level_sizes, conf }|
+            Derived { array, group_seeds, level_sizes, _conf: conf });
+    }
+
     fn test_hash2_invariants<GS: GroupSize, SS: SeedSize>(h: &GOFunction<GS, SS>) {
         let number_of_groups = h.level_sizes.iter().map(|v| *v as usize).sum::<usize>();
-        assert_eq!(h.conf.bits_per_group * number_of_groups, h.array.content.len() * 64);
+        assert_eq!(h.conf.bits_per_group * number_of_groups, h.array.content_words().len() * 64);
         assert_eq!(ceiling_div(number_of_groups * h.conf.bits_per_seed.into() as usize, 64), h.group_seeds.len());
     }
 
@@ -641,6 +679,7 @@ mod tests {
         test_mphf(to_hash, |key| h.get(key).map(|i| i as usize));
         test_hash2_invariants(&h);
         test_read_write(&h);
+        test_get_size(h);
     }
 
     #[test]
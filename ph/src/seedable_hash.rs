@@ -14,6 +14,12 @@ use std::hash::SipHasher13;
 pub trait BuildSeededHasher {
     type Hasher: Hasher;
 
+    /// Identifier of the family of hash functions, which is stored in the headers of serialized structures
+    /// in order to detect reading them with different hasher than the one used for writing.
+    /// `0` (default) means the family without assigned identifier, which is not checked when reading
+    /// (so the structures written with any hasher can be read with such family, and vice versa).
+    const ID: u8 = 0;
+
     /// Creates a new hasher initialized with the given `seed`.
     fn build_hasher(&self, seed: u32) -> Self::Hasher;
 
@@ -27,6 +33,8 @@ pub trait BuildSeededHasher {
 }
 
 /// [`BuildSeededHasher`] that uses standard [`BuildHasher`].
+/// As [`Seedable`] can wrap any [`BuildHasher`], whose identity is unknown,
+/// its [`BuildSeededHasher::ID`] is `0` and thus it is not checked when reading.
 #[derive(Default, Copy, Clone)]
 pub struct Seedable<BH: BuildHasher>(BH);
 
@@ -49,6 +57,7 @@ pub struct BuildSip13;
 #[allow(deprecated)]
 impl BuildSeededHasher for BuildSip13 {
     type Hasher = SipHasher13;
+    const ID: u8 = 2;
 
     #[inline] fn build_hasher(&self, seed: u32) -> Self::Hasher {
         Self::Hasher::new_with_keys(seed as u64, seed as u64)
@@ -62,6 +71,7 @@ pub struct BuildWyHash;
 #[cfg(feature = "wyhash")]
 impl BuildSeededHasher for BuildWyHash {
     type Hasher = wyhash::WyHash;
+    const ID: u8 = 3;
 
     #[inline] fn build_hasher(&self, seed: u32) -> Self::Hasher {
         Self::Hasher::with_seed(seed as u64)
@@ -84,6 +94,7 @@ impl BuildSeededHasher for BuildWyHashGit {
 #[cfg(feature = "fnv")]
 impl BuildSeededHasher for fnv::FnvBuildHasher {
     type Hasher = fnv::FnvHasher;
+    const ID: u8 = 4;
 
     #[inline] fn build_hasher(&self, seed: u32) -> Self::Hasher {
         Self::Hasher::with_key(seed as u64)
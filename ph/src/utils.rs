@@ -1,12 +1,39 @@
 //! Utility functions.
 
 use binout::{AsIs, Serializer};
-#[cfg(feature = "simple_rank")] use bitm::ArrayWithRankSimple;
-#[cfg(not(feature = "simple_rank"))] use bitm::ArrayWithRank101111;
-use bitm::ceiling_div;
+use bitm::{BitArrayWithRank, ceiling_div};
 
-#[cfg(feature = "simple_rank")] pub type ArrayWithRank = ArrayWithRankSimple;
-#[cfg(not(feature = "simple_rank"))] pub type ArrayWithRank = ArrayWithRank101111;
+/// Bit array with rank structure used by the functions of this crate.
+/// It is [`bitm::ArrayWithRankInterleaved`] if the `interleaved_rank` feature is enabled,
+/// [`bitm::ArrayWithRankSimple`] if the `simple_rank` feature is enabled,
+/// and [`bitm::ArrayWithRank101111`] otherwise.
+/// The `simple_rank` and `interleaved_rank` features are mutually exclusive.
+#[cfg(feature = "interleaved_rank")] pub type ArrayWithRank = bitm::ArrayWithRankInterleaved;
+#[cfg(all(feature = "simple_rank", not(feature = "interleaved_rank")))] pub type ArrayWithRank = bitm::ArrayWithRankSimple;
+#[cfg(not(any(feature = "simple_rank", feature = "interleaved_rank")))] pub type ArrayWithRank = bitm::ArrayWithRank101111;
+
+#[cfg(all(feature = "simple_rank", feature = "interleaved_rank"))]
+compile_error!("the `simple_rank` and `interleaved_rank` features of ph are mutually exclusive, enable at most one of them");
+
+/// Constructs [`ArrayWithRank`] for the `content`, potentially using multiple threads.
+/// Returns also the number of ones in the `content`.
+pub fn build_array_with_rank(content: Box<[u64]>, use_multiple_threads: bool) -> (ArrayWithRank, u64) {
+    #[cfg(not(any(feature = "simple_rank", feature = "interleaved_rank")))]
+    if use_multiple_threads { return ArrayWithRank::build_par(content); }
+    #[cfg(any(feature = "simple_rank", feature = "interleaved_rank"))] let _ = use_multiple_threads;
+    <ArrayWithRank as BitArrayWithRank>::build(content)
+}
+
+/// Constructs [`ArrayWithRank`] for the `content`, using multiple threads if the current rayon thread pool has more than one.
+/// Returns also the number of ones in the `content`.
+///
+/// It is used to rebuild the rank structure of the deserialized structures.
+#[inline] pub fn build_array_with_rank_par(content: Box<[u64]>) -> (ArrayWithRank, u64) {
+    build_array_with_rank(content, rayon::current_num_threads() > 1)
+}
+
+/// Magic bytes that begin the [`Header`](binout::Header) of each structure serialized by this crate.
+pub const MAGIC: [u8; 4] = *b"BSph";
 
 /// Reads `number_of_bits` bits, rounded up to multiple of 64, from `input`.
 pub fn read_bits<R: std::io::Read + ?Sized>(input: &mut R, number_of_bits: usize) -> std::io::Result<Box<[u64]>> {